@@ -1,28 +1,59 @@
-use crate::utils::{Comparator, TransactionData, percentile};
+use crate::{
+    leaders::{self, LeaderBreakdown, LeaderSchedule},
+    overhead::{OverheadSnapshot, StageSnapshot},
+    runtime::LagSnapshot,
+    slots::{self, SlotAnalysis},
+    stats::{
+        self, ChiSquareTest, EndpointIntervals, Interval, PairwiseTest, SIGNIFICANCE_LEVEL,
+        SignatureSample,
+    },
+    timeseries::{self, Timeline},
+    utils::{Comparator, StreamSetup, TransactionData, TransactionTags, percentile},
+};
+use base64::Engine;
 use comfy_table::{ContentArrangement, Table};
+use hdrhistogram::{
+    Histogram,
+    serialization::{Serializer, V2DeflateSerializer},
+};
 use serde_json::{Map, Value, json};
 use std::cmp::Ordering;
 
 #[cfg(target_os = "windows")]
 #[inline]
-fn table_preset() -> &'static str {
+pub fn table_preset() -> &'static str {
     comfy_table::presets::ASCII_FULL
 }
 
 #[cfg(not(target_os = "windows"))]
 #[inline]
-fn table_preset() -> &'static str {
+pub fn table_preset() -> &'static str {
     comfy_table::presets::UTF8_FULL
 }
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
 
+/// Delays are histogrammed in microseconds up to ten minutes.
+const DELAY_HISTOGRAM_MAX_US: u64 = 600_000_000;
+/// Only the busiest accounts or programs are broken out.
+const MAX_GROUPS: usize = 10;
+/// Only the leaders with the most signatures are listed.
+const MAX_LEADERS_SHOWN: usize = 15;
+
 #[derive(Default)]
 pub struct EndpointStats {
     pub total_observations: usize,
     pub first_detections: usize,
     pub delays_ms: Vec<f64>,
     pub backfill_transactions: usize,
+    pub delivered: usize,
+    pub exclusive: usize,
+    pub late: usize,
+    /// Signatures this endpoint had not delivered while they were still
+    /// within the late threshold when observation ended.
+    pub pending: usize,
+    pub lead_times_ms: Vec<f64>,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -32,85 +63,474 @@ pub struct EndpointSummary {
     pub p50_delay_ms: Option<f64>,
     pub p95_delay_ms: Option<f64>,
     pub p99_delay_ms: Option<f64>,
+    /// Delay at each of the run's configured percentiles, in the same order.
+    pub delay_percentiles: Vec<Option<f64>>,
+    pub first_share_ci: Option<Interval>,
+    /// Bootstrap interval for each of `delay_percentiles`.
+    pub delay_percentile_cis: Vec<Option<Interval>>,
+    pub mean_delay_ms: Option<f64>,
+    pub stddev_delay_ms: Option<f64>,
+    pub max_delay_ms: Option<f64>,
+    /// Every delay-to-first sample, sorted ascending.
+    pub delays_ms: Vec<f64>,
+    /// The same samples in microseconds, for merging across runs.
+    pub delay_histogram: Option<Histogram<u64>>,
     pub valid_transactions: usize,
     pub first_detections: usize,
     pub backfill_transactions: usize,
+    /// Share of all signatures, including partial ones, this endpoint delivered.
+    pub coverage: f64,
+    pub delivered_signatures: usize,
+    /// Signatures some other endpoint delivered but this one did not, once
+    /// the late threshold, if set, has passed.
+    pub missed_signatures: usize,
+    /// Signatures this endpoint delivered after the late threshold.
+    pub late_signatures: usize,
+    /// Signatures no other endpoint delivered, on time or late.
+    pub exclusive_signatures: usize,
+    /// How far ahead of the runner-up this endpoint was when it came first.
+    pub lead_samples: usize,
+    pub lead_p50_ms: Option<f64>,
+    pub lead_p95_ms: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EndpointRuntimeSummary {
+    pub name: String,
+    pub placement: String,
+    /// Only for endpoints on a dedicated runtime.
+    pub event_loop_lag: Option<LagSnapshot>,
+    pub overhead: OverheadSnapshot,
 }
 
 #[derive(Debug, Clone)]
 pub struct RunSummary {
     pub endpoints: Vec<EndpointSummary>,
+    /// Lowest-P50 endpoint, set only when it beat every other endpoint in a
+    /// significant sign test.
     pub fastest_endpoint: Option<String>,
     pub has_data: bool,
     pub total_signatures: usize,
     pub backfill_signatures: usize,
+    /// Signatures delivered by at least one endpoint.
+    pub union_signatures: usize,
+    pub percentiles: Vec<f64>,
+    pub pairwise: Vec<PairwiseTest>,
+    pub timeline: Timeline,
+    pub slots: SlotAnalysis,
+    /// Summaries restricted to one account, program or class each.
+    pub groups: Vec<GroupSummary>,
+    pub class_tests: Vec<ClassTest>,
+    /// Set when a leader schedule was loaded.
+    pub leaders: Option<LeaderBreakdown>,
+    pub runtimes: Vec<EndpointRuntimeSummary>,
+    /// Lag of the runtime shared by every non-dedicated endpoint.
+    pub shared_runtime_lag: Option<LagSnapshot>,
+    /// When each endpoint connected and subscribed, for live runs.
+    pub stream_setups: Vec<StreamSetup>,
+    /// When every endpoint was subscribed and counting began.
+    pub counting_started_at: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    Account,
+    Program,
+    /// Succeeded or failed, for feeds that carry execution status.
+    Status,
+    /// Compute-unit price bucket.
+    CuPrice,
+    /// Whether the transaction references a Jito tip account.
+    JitoTip,
+}
+
+/// Upper bounds, in micro-lamports per compute unit, of each price class
+/// after "none".
+const CU_PRICE_BOUNDS: [(u64, &str); 4] = [
+    (1_000, "< 1k"),
+    (10_000, "1k-10k"),
+    (100_000, "10k-100k"),
+    (1_000_000, "100k-1M"),
+];
+const CU_PRICE_CLASSES: [&str; 6] = ["none", "< 1k", "1k-10k", "10k-100k", "100k-1M", ">= 1M"];
+
+impl GroupBy {
+    const ALL: [GroupBy; 5] = [
+        GroupBy::Account,
+        GroupBy::Program,
+        GroupBy::Status,
+        GroupBy::CuPrice,
+        GroupBy::JitoTip,
+    ];
+
+    pub fn from_arg(value: &str) -> Option<Self> {
+        match value {
+            "account" => Some(GroupBy::Account),
+            "program" => Some(GroupBy::Program),
+            "status" => Some(GroupBy::Status),
+            "cu-price" => Some(GroupBy::CuPrice),
+            "tip" => Some(GroupBy::JitoTip),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GroupBy::Account => "account",
+            GroupBy::Program => "program",
+            GroupBy::Status => "status",
+            GroupBy::CuPrice => "cu-price",
+            GroupBy::JitoTip => "tip",
+        }
+    }
+
+    fn noun(self) -> &'static str {
+        match self {
+            GroupBy::Account => "account",
+            GroupBy::Program => "program",
+            GroupBy::Status => "execution status",
+            GroupBy::CuPrice => "compute-unit price",
+            GroupBy::JitoTip => "Jito tip",
+        }
+    }
+
+    fn header(self) -> &'static str {
+        match self {
+            GroupBy::Account => "Account",
+            GroupBy::Program => "Program",
+            GroupBy::Status => "Status",
+            GroupBy::CuPrice => "CU price µlamports",
+            GroupBy::JitoTip => "Jito tip",
+        }
+    }
+
+    /// Fixed classes, in display order, for dimensions where every
+    /// transaction falls in at most one.
+    fn classes(self) -> Option<&'static [&'static str]> {
+        match self {
+            GroupBy::Account | GroupBy::Program => None,
+            GroupBy::Status => Some(&["succeeded", "failed"]),
+            GroupBy::CuPrice => Some(&CU_PRICE_CLASSES),
+            GroupBy::JitoTip => Some(&["tip", "no tip"]),
+        }
+    }
+
+    /// Price and tip need the instructions, which account updates lack.
+    fn keys(self, tags: &TransactionTags) -> Vec<String> {
+        let has_instructions = !tags.programs.is_empty();
+        match self {
+            GroupBy::Account => tags.accounts.clone(),
+            GroupBy::Program => tags.programs.clone(),
+            GroupBy::Status => tags
+                .succeeded
+                .map(|succeeded| if succeeded { "succeeded" } else { "failed" }.to_string())
+                .into_iter()
+                .collect(),
+            GroupBy::CuPrice if has_instructions => vec![cu_price_class(tags.cu_price).to_string()],
+            GroupBy::JitoTip if has_instructions => {
+                vec![if tags.jito_tip { "tip" } else { "no tip" }.to_string()]
+            }
+            GroupBy::CuPrice | GroupBy::JitoTip => Vec::new(),
+        }
+    }
+}
+
+fn cu_price_class(price: Option<u64>) -> &'static str {
+    match price.filter(|price| *price > 0) {
+        None => CU_PRICE_CLASSES[0],
+        Some(price) => CU_PRICE_BOUNDS
+            .iter()
+            .find(|(bound, _)| price < *bound)
+            .map_or(CU_PRICE_CLASSES[5], |(_, class)| class),
+    }
+}
+
+/// Whether the endpoint that came first depends on the transaction class.
+#[derive(Debug, Clone)]
+pub struct ClassTest {
+    pub group_by: GroupBy,
+    pub test: ChiSquareTest,
+}
+
+#[derive(Debug, Clone)]
+pub struct GroupSummary {
+    pub group_by: GroupBy,
+    pub key: String,
+    pub summary: RunSummary,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnalysisOptions {
+    /// Delay percentiles shown per endpoint, as fractions in `(0, 1]`; `1.0`
+    /// is the maximum.
+    pub percentiles: Vec<f64>,
+    /// Signatures first seen inside any of these windows, in seconds since the
+    /// run started, are left out of the summary.
+    pub excluded_ranges: Vec<(f64, f64)>,
+    /// Deliveries more than this many milliseconds after the first endpoint
+    /// are counted as late instead of as delay samples.
+    pub late_threshold_ms: Option<f64>,
+    /// End of the observation period. Signatures first seen more than the
+    /// late threshold before it are summarised even if some endpoint never
+    /// delivered them. Defaults to the last recorded arrival.
+    pub observed_until: Option<Duration>,
+    /// Width of the time-series buckets, in seconds.
+    pub bucket_secs: f64,
+    /// Also summarise each account, program, execution status, compute-unit
+    /// price class or Jito tip class on its own.
+    pub group_by: Vec<GroupBy>,
+    /// Restricts the summary to signatures tagged with this key.
+    pub only: Option<(GroupBy, String)>,
+    /// Maps slots to leaders for a per-leader breakdown.
+    pub leader_schedule: Option<Arc<LeaderSchedule>>,
+}
+
+impl Default for AnalysisOptions {
+    fn default() -> Self {
+        Self {
+            percentiles: vec![0.5, 0.95, 0.99],
+            excluded_ranges: Vec::new(),
+            late_threshold_ms: None,
+            observed_until: None,
+            bucket_secs: timeseries::DEFAULT_BUCKET_SECS,
+            group_by: Vec::new(),
+            only: None,
+            leader_schedule: None,
+        }
+    }
+}
+
+impl AnalysisOptions {
+    fn admits(&self, sig_data: &HashMap<&String, &TransactionData>) -> bool {
+        match &self.only {
+            Some((group_by, key)) => sig_data
+                .values()
+                .any(|tx| group_by.keys(&tx.tags).contains(key)),
+            None => true,
+        }
+    }
+
+    fn excludes(&self, elapsed: Duration) -> bool {
+        let secs = elapsed.as_secs_f64();
+        self.excluded_ranges
+            .iter()
+            .any(|(start, end)| secs >= *start && secs < *end)
+    }
 }
 
-pub fn compute_run_summary(comparator: &Comparator, endpoint_names: &[String]) -> RunSummary {
+/// Summarises the signatures seen by every endpoint in `endpoint_names`.
+/// Observations from other endpoints in the comparator are ignored.
+pub fn compute_run_summary(
+    comparator: &Comparator,
+    endpoint_names: &[String],
+    options: &AnalysisOptions,
+) -> RunSummary {
     let mut endpoint_stats: HashMap<String, EndpointStats> = HashMap::new();
     let expected_producers = endpoint_names.len();
     let mut total_signatures = 0usize;
     let mut backfill_signatures = 0usize;
+    let mut union_signatures = 0usize;
+    let mut samples: Vec<SignatureSample> = Vec::new();
 
     for endpoint_name in endpoint_names {
         endpoint_stats.insert(endpoint_name.clone(), EndpointStats::default());
     }
 
+    let late_threshold_ms = options
+        .late_threshold_ms
+        .filter(|ms| ms.is_finite() && *ms >= 0.0);
+    let observed_until = match (late_threshold_ms, options.observed_until) {
+        (None, _) => Duration::ZERO,
+        (Some(_), Some(until)) => until,
+        (Some(_), None) => comparator
+            .iter()
+            .flat_map(|entry| {
+                entry
+                    .value()
+                    .iter()
+                    .filter(|(endpoint, _)| endpoint_stats.contains_key(*endpoint))
+                    .map(|(_, tx)| tx.arrival_elapsed())
+                    .max()
+            })
+            .max()
+            .unwrap_or_default(),
+    };
+
     for sig_entry in comparator.iter() {
-        let sig_data = sig_entry.value();
-        if expected_producers > 0 && sig_data.len() != expected_producers {
-            // Skip partial observations to mirror backend results
+        let sig_data: HashMap<&String, &TransactionData> = sig_entry
+            .value()
+            .iter()
+            .filter(|(endpoint, _)| endpoint_stats.contains_key(*endpoint))
+            .collect();
+        if sig_data.is_empty() || !options.admits(&sig_data) {
             continue;
         }
+        let complete = sig_data.len() == expected_producers;
 
         let is_historical = sig_data
             .values()
             .any(|tx| tx.wallclock_secs < tx.start_wallclock_secs);
 
         if is_historical {
-            backfill_signatures += 1;
-            for endpoint in sig_data.keys() {
-                if let Some(stats) = endpoint_stats.get_mut(endpoint) {
-                    stats.backfill_transactions += 1;
+            if complete {
+                backfill_signatures += 1;
+                for endpoint in sig_data.keys() {
+                    if let Some(stats) = endpoint_stats.get_mut(*endpoint) {
+                        stats.backfill_transactions += 1;
+                    }
                 }
             }
             continue;
         }
 
         let Some((first_endpoint, first_tx)) =
-            sig_data.iter().min_by_key(|(_, tx)| tx.elapsed_since_start)
+            sig_data.iter().min_by_key(|(_, tx)| tx.arrival_elapsed())
         else {
             continue;
         };
 
+        if options.excludes(first_tx.arrival_elapsed()) {
+            continue;
+        }
+
+        union_signatures += 1;
+        let on_time: Vec<(&String, f64)> = sig_data
+            .iter()
+            .filter_map(|(endpoint, tx)| {
+                let delay_ms = diff_ms(tx, first_tx).max(0.0);
+                if late_threshold_ms.is_some_and(|threshold| delay_ms > threshold) {
+                    if let Some(stats) = endpoint_stats.get_mut(*endpoint) {
+                        stats.late += 1;
+                    }
+                    None
+                } else {
+                    Some((*endpoint, delay_ms))
+                }
+            })
+            .collect();
+        let exclusive = sig_data.len() == 1;
+        for (endpoint, _) in &on_time {
+            if let Some(stats) = endpoint_stats.get_mut(*endpoint) {
+                stats.delivered += 1;
+                if exclusive {
+                    stats.exclusive += 1;
+                }
+            }
+        }
+
+        // Latency is only compared on signatures every endpoint delivered,
+        // mirroring backend results, unless the late threshold has passed.
+        let timed_out = late_threshold_ms.is_some_and(|threshold| {
+            first_tx.arrival_elapsed() + Duration::from_secs_f64(threshold / 1_000.0)
+                <= observed_until
+        });
+        if !complete && !timed_out {
+            // Endpoints still had time to deliver it, so it is not a miss yet.
+            if late_threshold_ms.is_some() {
+                for (name, stats) in endpoint_stats.iter_mut() {
+                    if !sig_data.contains_key(name) {
+                        stats.pending += 1;
+                    }
+                }
+            }
+            continue;
+        }
+
         total_signatures += 1;
-        let first_endpoint_name = first_endpoint.clone();
+        let mut sample = SignatureSample {
+            at_secs: first_tx.arrival_elapsed().as_secs_f64(),
+            first: 0,
+            delays_ms: vec![None; expected_producers],
+            slots: vec![None; expected_producers],
+        };
+        for (index, name) in endpoint_names.iter().enumerate() {
+            if name == *first_endpoint {
+                sample.first = index;
+            }
+            sample.slots[index] = sig_data.get(name).and_then(|tx| tx.slot);
+            sample.delays_ms[index] = on_time
+                .iter()
+                .find(|(endpoint, _)| *endpoint == name)
+                .map(|(_, delay_ms)| *delay_ms);
+        }
+        samples.push(sample);
+
+        let runner_up_ms = on_time
+            .iter()
+            .filter(|(endpoint, _)| *endpoint != *first_endpoint)
+            .map(|(_, delay_ms)| *delay_ms)
+            .min_by(f64::total_cmp);
+        if let Some(lead_ms) = runner_up_ms
+            && let Some(stats) = endpoint_stats.get_mut(*first_endpoint)
+        {
+            stats.lead_times_ms.push(lead_ms);
+        }
 
-        for (endpoint, tx) in sig_data.iter() {
+        for (endpoint, delay_ms) in on_time {
             if let Some(stats) = endpoint_stats.get_mut(endpoint) {
                 stats.total_observations += 1;
-                if endpoint == &first_endpoint_name {
+                if endpoint == *first_endpoint {
                     stats.first_detections += 1;
                     stats.delays_ms.push(0.0);
                 } else {
-                    let delay_ms = diff_ms(tx, first_tx).max(0.0);
                     stats.delays_ms.push(delay_ms);
                 }
             }
         }
     }
 
+    // The comparator iterates in arbitrary order; sorting keeps the seeded
+    // bootstrap reproducible when the same observations are analysed again.
+    samples.sort_by(|a, b| {
+        a.at_secs
+            .total_cmp(&b.at_secs)
+            .then(a.first.cmp(&b.first))
+            .then_with(|| {
+                let bits = |delays: &[Option<f64>]| -> Vec<Option<u64>> {
+                    delays.iter().map(|delay| delay.map(f64::to_bits)).collect()
+                };
+                bits(&a.delays_ms).cmp(&bits(&b.delays_ms))
+            })
+    });
+    let mut intervals =
+        stats::bootstrap_intervals(&samples, expected_producers, &options.percentiles);
     let endpoints: Vec<EndpointSummary> = endpoint_stats
         .into_iter()
-        .map(|(endpoint, stats)| build_summary(endpoint, stats, total_signatures))
+        .map(|(endpoint, stats)| {
+            let index = endpoint_names
+                .iter()
+                .position(|name| *name == endpoint)
+                .unwrap_or_default();
+            build_summary(
+                endpoint,
+                stats,
+                std::mem::take(&mut intervals[index]),
+                total_signatures,
+                union_signatures,
+                &options.percentiles,
+            )
+        })
         .collect();
 
     let has_data = total_signatures > 0;
+    let pairwise = stats::pairwise_sign_tests(&samples, endpoint_names);
+    let timeline = timeseries::build_timeline(&samples, endpoint_names, options.bucket_secs);
+    let slots = slots::build_slot_analysis(&samples, endpoint_names);
+    let (groups, class_tests) = match options.only {
+        Some(_) => (Vec::new(), Vec::new()),
+        None => compute_groups(comparator, endpoint_names, options),
+    };
+    let leaders = options
+        .leader_schedule
+        .as_deref()
+        .filter(|_| options.only.is_none())
+        .map(|schedule| leaders::build_leader_breakdown(&samples, endpoint_names, schedule));
 
     let fastest_endpoint = endpoints
         .iter()
         .filter(|summary| summary.valid_transactions > 0)
         .min_by(|a, b| compare_latency(a, b))
+        .filter(|leader| leads_significantly(&leader.name, &pairwise))
         .map(|summary| summary.name.clone());
 
     RunSummary {
@@ -119,6 +539,150 @@ pub fn compute_run_summary(comparator: &Comparator, endpoint_names: &[String]) -
         has_data,
         total_signatures,
         backfill_signatures,
+        union_signatures,
+        percentiles: options.percentiles.clone(),
+        pairwise,
+        timeline,
+        slots,
+        groups,
+        class_tests,
+        leaders,
+        runtimes: Vec::new(),
+        shared_runtime_lag: None,
+        stream_setups: Vec::new(),
+        counting_started_at: None,
+    }
+}
+
+/// Summarises the busiest accounts and programs, and every class, separately.
+/// A signature counts towards a key when any endpoint tagged it with that
+/// key. For class dimensions, also tests whether the first endpoint depends
+/// on the class.
+fn compute_groups(
+    comparator: &Comparator,
+    endpoint_names: &[String],
+    options: &AnalysisOptions,
+) -> (Vec<GroupSummary>, Vec<ClassTest>) {
+    let mut groups = Vec::new();
+    let mut class_tests = Vec::new();
+    for group_by in &options.group_by {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for entry in comparator.iter() {
+            let mut keys: Vec<String> = entry
+                .value()
+                .iter()
+                .filter(|(endpoint, _)| endpoint_names.contains(endpoint))
+                .flat_map(|(_, tx)| group_by.keys(&tx.tags))
+                .collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                *counts.entry(key).or_default() += 1;
+            }
+        }
+
+        let mut keys: Vec<(String, usize)> = counts.into_iter().collect();
+        match group_by.classes() {
+            Some(classes) => keys.sort_by_key(|(key, _)| classes.iter().position(|c| c == key)),
+            None => keys.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0))),
+        }
+        let first = groups.len();
+        for (key, _) in keys.into_iter().take(MAX_GROUPS) {
+            let group_options = AnalysisOptions {
+                group_by: Vec::new(),
+                only: Some((*group_by, key.clone())),
+                ..options.clone()
+            };
+            let summary = compute_run_summary(comparator, endpoint_names, &group_options);
+            if summary.has_data {
+                groups.push(GroupSummary {
+                    group_by: *group_by,
+                    key,
+                    summary,
+                });
+            }
+        }
+
+        if group_by.classes().is_some() {
+            let firsts: Vec<Vec<usize>> = groups[first..]
+                .iter()
+                .map(|group| {
+                    endpoint_names
+                        .iter()
+                        .map(|name| {
+                            group
+                                .summary
+                                .endpoints
+                                .iter()
+                                .find(|endpoint| endpoint.name == *name)
+                                .map_or(0, |endpoint| endpoint.first_detections)
+                        })
+                        .collect()
+                })
+                .collect();
+            if let Some(test) = stats::chi_square_independence(&firsts) {
+                class_tests.push(ClassTest {
+                    group_by: *group_by,
+                    test,
+                });
+            }
+        }
+    }
+    (groups, class_tests)
+}
+
+/// Whether `leader` won a significant majority against every other endpoint,
+/// with a Bonferroni correction for the number of comparisons.
+fn leads_significantly(leader: &str, pairwise: &[PairwiseTest]) -> bool {
+    let tests: Vec<&PairwiseTest> = pairwise
+        .iter()
+        .filter(|test| test.involves(leader))
+        .collect();
+    let alpha = SIGNIFICANCE_LEVEL / tests.len().max(1) as f64;
+    tests.iter().all(|test| test.winner(alpha) == Some(leader))
+}
+
+/// End of the warmup period in seconds since run start: `warmup_secs` after
+/// `connected_at`, or just past the first `warmup_signatures` signatures seen
+/// by every endpoint after `connected_at`, whichever is later.
+pub fn warmup_cutoff(
+    comparator: &Comparator,
+    endpoint_names: &[String],
+    connected_at: Duration,
+    warmup_secs: Option<f64>,
+    warmup_signatures: Option<usize>,
+) -> Option<f64> {
+    let by_time = warmup_secs.map(|secs| connected_at.as_secs_f64() + secs.max(0.0));
+    let by_count = warmup_signatures.filter(|count| *count > 0).map(|count| {
+        let mut arrivals: Vec<Duration> = comparator
+            .iter()
+            .filter_map(|entry| {
+                let observations = entry.value();
+                let complete = endpoint_names
+                    .iter()
+                    .all(|name| observations.contains_key(name));
+                complete
+                    .then(|| {
+                        endpoint_names
+                            .iter()
+                            .filter_map(|name| observations.get(name))
+                            .map(TransactionData::arrival_elapsed)
+                            .min()
+                    })
+                    .flatten()
+            })
+            .filter(|first| *first >= connected_at)
+            .collect();
+        arrivals.sort();
+        arrivals
+            .get(count)
+            .map(Duration::as_secs_f64)
+            .unwrap_or(f64::INFINITY)
+    });
+
+    match (by_time, by_count) {
+        (Some(time), Some(count)) => Some(time.max(count)),
+        (time, count) => time.or(count),
     }
 }
 
@@ -145,20 +709,26 @@ pub fn display_run_summary(summary: &RunSummary) {
             } else {
                 format!("{}%", raw_win_rate)
             };
-            let is_fastest = fastest_name_ref == Some(summary.name.as_str());
-
-            if is_fastest {
-                println!(
-                    "{}: Win rate {}, p50 0.00ms (fastest)",
-                    summary.name, win_rate,
-                );
+            let fastest = if fastest_name_ref == Some(summary.name.as_str()) {
+                " (fastest)"
             } else {
-                let p50_delay = summary
-                    .p50_delay_ms
-                    .map(|v| format!("{:.2}ms", v))
-                    .unwrap_or_else(|| "—".to_string());
-                println!("{}: Win rate {}, p50 {}", summary.name, win_rate, p50_delay);
-            }
+                ""
+            };
+            let p50_delay = summary
+                .p50_delay_ms
+                .map(|v| format!("{:.2}ms", v))
+                .unwrap_or_else(|| "—".to_string());
+            println!(
+                "{}: Win rate {}, p50 {}{}",
+                summary.name, win_rate, p50_delay, fastest
+            );
+        }
+
+        if fastest_name_ref.is_none() && summary.endpoints.len() > 1 {
+            println!(
+                "No endpoint was significantly faster than every other (sign test, p < {})",
+                SIGNIFICANCE_LEVEL
+            );
         }
     }
 
@@ -176,73 +746,963 @@ pub fn display_run_summary(summary: &RunSummary) {
     let mut table = Table::new();
     table.load_preset(table_preset());
     table.set_content_arrangement(ContentArrangement::Dynamic);
-    table.set_header(vec![
-        "Endpoint", "First %", "P50 ms", "P95 ms", "P99 ms", "Valid Tx", "Firsts", "Backfill",
-    ]);
+    let mut header = vec![
+        "Endpoint".to_string(),
+        "First %".to_string(),
+        "Coverage %".to_string(),
+    ];
+    header.extend(
+        summary
+            .percentiles
+            .iter()
+            .map(|quantile| format!("{} ms", percentile_label(*quantile))),
+    );
+    header.push("Mean ± SD ms".to_string());
+    header.extend(
+        [
+            "Valid Tx",
+            "Firsts",
+            "Missed",
+            "Late",
+            "Exclusive",
+            "Backfill",
+        ]
+        .map(String::from),
+    );
+    table.set_header(header);
 
-    let fastest_name_ref = summary.fastest_endpoint.as_deref();
     for summary in table_rows {
-        let is_fastest = fastest_name_ref == Some(summary.name.as_str());
-        table.add_row(vec![
+        let mut row = vec![
             summary.name.clone(),
             format_percent(summary.first_share),
-            format_latency_value(summary.p50_delay_ms, is_fastest),
-            format_latency_value(summary.p95_delay_ms, is_fastest),
-            format_latency_value(summary.p99_delay_ms, is_fastest),
+            format_percent(summary.coverage),
+        ];
+        row.extend(
+            summary
+                .delay_percentiles
+                .iter()
+                .map(|value| format_latency_value(*value)),
+        );
+        row.push(format_mean_stddev(summary));
+        row.extend([
             summary.valid_transactions.to_string(),
             summary.first_detections.to_string(),
+            summary.missed_signatures.to_string(),
+            summary.late_signatures.to_string(),
+            summary.exclusive_signatures.to_string(),
             summary.backfill_transactions.to_string(),
         ]);
+        table.add_row(row);
     }
 
     println!("{}", table);
 }
 
+/// N×N matrix: how often the row endpoint beat the column endpoint on the
+/// signatures both delivered, and the row's median lead over the column.
+pub fn display_head_to_head(summary: &RunSummary) {
+    if !summary.has_data || summary.pairwise.is_empty() {
+        return;
+    }
+
+    println!("\nHead to head (row beat column %, median row − column ms)");
+    println!("--------------------------------------------");
+
+    let mut names: Vec<&EndpointSummary> = summary.endpoints.iter().collect();
+    names.sort_by(|a, b| compare_latency(a, b));
+    let names: Vec<&str> = names
+        .iter()
+        .map(|endpoint| endpoint.name.as_str())
+        .collect();
+
+    let mut table = Table::new();
+    table.load_preset(table_preset());
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    let mut header = vec![String::new()];
+    header.extend(names.iter().map(|name| name.to_string()));
+    table.set_header(header);
+
+    for row_name in &names {
+        let mut row = vec![row_name.to_string()];
+        row.extend(names.iter().map(|column_name| {
+            match pair_test(summary, row_name, column_name)
+                .and_then(|test| test.seen_from(row_name))
+            {
+                Some((win_rate, delta)) => format!(
+                    "{}% / {}",
+                    format_percent(win_rate),
+                    delta
+                        .map(|delta| format!("{:+.2}", delta))
+                        .unwrap_or_else(|| "—".to_string())
+                ),
+                None => "—".to_string(),
+            }
+        }));
+        table.add_row(row);
+    }
+    println!("{}", table);
+}
+
+fn pair_test<'a>(summary: &'a RunSummary, lhs: &str, rhs: &str) -> Option<&'a PairwiseTest> {
+    if lhs == rhs {
+        return None;
+    }
+    summary
+        .pairwise
+        .iter()
+        .find(|test| test.involves(lhs) && test.involves(rhs))
+}
+
+pub fn display_significance(summary: &RunSummary) {
+    if !summary.has_data {
+        return;
+    }
+
+    println!(
+        "\nConfidence intervals ({:.0}%, bootstrap over signatures)",
+        stats::CONFIDENCE_LEVEL * 100.0
+    );
+    println!("--------------------------------------------");
+
+    let mut table = Table::new();
+    table.load_preset(table_preset());
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    let mut header = vec!["Endpoint".to_string(), "First %".to_string()];
+    header.extend(
+        summary
+            .percentiles
+            .iter()
+            .map(|quantile| format!("{} ms", percentile_label(*quantile))),
+    );
+    table.set_header(header);
+
+    let mut rows: Vec<&EndpointSummary> = summary.endpoints.iter().collect();
+    rows.sort_by(|a, b| compare_latency(a, b));
+    for endpoint in rows {
+        let mut row = vec![
+            endpoint.name.clone(),
+            format_interval(endpoint.first_share_ci, 100.0),
+        ];
+        row.extend(
+            endpoint
+                .delay_percentile_cis
+                .iter()
+                .map(|interval| format_interval(*interval, 1.0)),
+        );
+        table.add_row(row);
+    }
+    println!("{}", table);
+
+    if summary.pairwise.is_empty() {
+        return;
+    }
+
+    println!("\nPairwise sign test");
+    println!("--------------------------------------------");
+
+    let mut table = Table::new();
+    table.load_preset(table_preset());
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec![
+        "Endpoints",
+        "First faster",
+        "Second faster",
+        "Ties",
+        "p-value",
+        "Verdict",
+    ]);
+    for test in &summary.pairwise {
+        let verdict = match test.winner(SIGNIFICANCE_LEVEL) {
+            Some(winner) => format!("{} faster", winner),
+            None => "not significant".to_string(),
+        };
+        table.add_row(vec![
+            format!("{} vs {}", test.lhs, test.rhs),
+            test.lhs_faster.to_string(),
+            test.rhs_faster.to_string(),
+            test.ties.to_string(),
+            format_p_value(test.p_value),
+            verdict,
+        ]);
+    }
+    println!("{}", table);
+}
+
+fn format_p_value(p_value: f64) -> String {
+    if p_value < 1e-4 {
+        "< 0.0001".to_string()
+    } else {
+        format!("{:.4}", p_value)
+    }
+}
+
+fn format_interval(interval: Option<Interval>, scale: f64) -> String {
+    interval
+        .map(|interval| format!("{:.2} – {:.2}", interval.low * scale, interval.high * scale))
+        .unwrap_or_else(|| "—".to_string())
+}
+
+pub fn display_incidents(summary: &RunSummary) {
+    if !summary.has_data {
+        return;
+    }
+
+    let timeline = &summary.timeline;
+    if timeline.incidents.is_empty() {
+        println!(
+            "\nNo latency incidents detected ({}s buckets)",
+            timeline.bucket_secs
+        );
+        return;
+    }
+
+    println!(
+        "\nLatency incidents ({}s buckets, P95 well above the endpoint's own baseline)",
+        timeline.bucket_secs
+    );
+    println!("--------------------------------------------");
+
+    let mut table = Table::new();
+    table.load_preset(table_preset());
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec![
+        "Endpoint",
+        "Start s",
+        "End s",
+        "Baseline P95 ms",
+        "Peak P95 ms",
+    ]);
+    for incident in &timeline.incidents {
+        table.add_row(vec![
+            incident.endpoint.clone(),
+            format!("{:.0}", incident.start_secs),
+            format!("{:.0}", incident.end_secs),
+            format!("{:.2}", incident.baseline_p95_ms),
+            format!("{:.2}", incident.peak_p95_ms),
+        ]);
+    }
+    println!("{}", table);
+}
+
+/// The detailed results table once per tracked account, invoked program,
+/// execution status, compute-unit price class or Jito tip class.
+pub fn display_groups(summary: &RunSummary) {
+    for group_by in GroupBy::ALL {
+        let groups: Vec<&GroupSummary> = summary
+            .groups
+            .iter()
+            .filter(|group| group.group_by == group_by)
+            .collect();
+        if groups.is_empty() {
+            continue;
+        }
+
+        println!("\nResults by {}", group_by.noun());
+        println!("--------------------------------------------");
+
+        let mut table = Table::new();
+        table.load_preset(table_preset());
+        table.set_content_arrangement(ContentArrangement::Dynamic);
+        let mut header = vec![
+            group_by.header().to_string(),
+            "Signatures".to_string(),
+            "Endpoint".to_string(),
+            "First %".to_string(),
+        ];
+        header.extend(
+            summary
+                .percentiles
+                .iter()
+                .map(|quantile| format!("{} ms", percentile_label(*quantile))),
+        );
+        header.push("Missed".to_string());
+        table.set_header(header);
+
+        for group in groups {
+            let mut rows: Vec<&EndpointSummary> = group.summary.endpoints.iter().collect();
+            rows.sort_by(|a, b| compare_latency(a, b));
+            for (index, endpoint) in rows.into_iter().enumerate() {
+                let (key, signatures) = if index == 0 {
+                    (
+                        group.key.clone(),
+                        group.summary.total_signatures.to_string(),
+                    )
+                } else {
+                    (String::new(), String::new())
+                };
+                let mut row = vec![
+                    key,
+                    signatures,
+                    endpoint.name.clone(),
+                    format_percent(endpoint.first_share),
+                ];
+                row.extend(
+                    endpoint
+                        .delay_percentiles
+                        .iter()
+                        .map(|value| format_latency_value(*value)),
+                );
+                row.push(endpoint.missed_signatures.to_string());
+                table.add_row(row);
+            }
+        }
+        println!("{}", table);
+
+        let Some(class_test) = summary
+            .class_tests
+            .iter()
+            .find(|class_test| class_test.group_by == group_by)
+        else {
+            continue;
+        };
+        let test = &class_test.test;
+        let verdict = if test.p_value < SIGNIFICANCE_LEVEL {
+            "depends on"
+        } else {
+            "does not significantly depend on"
+        };
+        println!(
+            "Which endpoint comes first {} the {} (chi-square {:.2}, {} df, p-value {})",
+            verdict,
+            group_by.noun(),
+            test.statistic,
+            test.degrees_of_freedom,
+            format_p_value(test.p_value)
+        );
+    }
+}
+
+/// Per-slot wins, wins and delay by position within the slot, and how often
+/// endpoints disagreed about the slot.
+pub fn display_slots(summary: &RunSummary) {
+    let slots = &summary.slots;
+    if !summary.has_data || slots.signatures_with_slot == 0 {
+        return;
+    }
+
+    println!(
+        "\nSlot attribution ({} slots, {} of {} signatures reported in different slots)",
+        slots.slots.len(),
+        slots.disagreements,
+        slots.signatures_with_slot
+    );
+    println!("--------------------------------------------");
+
+    let mut table = Table::new();
+    table.load_preset(table_preset());
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec!["Endpoint", "Slots led", "Slot mismatches"]);
+    let mut rows: Vec<(&String, &slots::EndpointSlotStats)> =
+        slots.endpoint_names.iter().zip(&slots.endpoints).collect();
+    rows.sort_by(|a, b| b.1.slots_led.cmp(&a.1.slots_led).then(a.0.cmp(b.0)));
+    for (name, stats) in rows {
+        let mismatch_rate = if stats.reported > 0 {
+            stats.mismatches as f64 / stats.reported as f64
+        } else {
+            0.0
+        };
+        table.add_row(vec![
+            name.clone(),
+            format!(
+                "{} ({}%)",
+                stats.slots_led,
+                format_percent(stats.slots_led as f64 / slots.slots.len() as f64)
+            ),
+            format!("{} ({}%)", stats.mismatches, format_percent(mismatch_rate)),
+        ]);
+    }
+    println!("{}", table);
+
+    println!("\nBy position in slot (win rate / P50 delay ms)");
+    println!("--------------------------------------------");
+
+    let mut table = Table::new();
+    table.load_preset(table_preset());
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    let mut header = vec!["Position ms".to_string(), "Signatures".to_string()];
+    header.extend(slots.endpoint_names.iter().cloned());
+    table.set_header(header);
+    for band in slots.positions.iter().filter(|band| band.signatures > 0) {
+        let mut row = vec![
+            match band.end_ms {
+                Some(end_ms) => format!("{:.0}-{:.0}", band.start_ms, end_ms),
+                None => format!("{:.0}+", band.start_ms),
+            },
+            band.signatures.to_string(),
+        ];
+        for stats in &band.endpoints {
+            row.push(format!(
+                "{}% / {}",
+                format_percent(stats.firsts as f64 / band.signatures as f64),
+                format_latency_value(stats.p50_delay_ms)
+            ));
+        }
+        table.add_row(row);
+    }
+    println!("{}", table);
+}
+
+/// Per-leader win rates and P50 delays, and the leaders an endpoint was
+/// consistently slow on.
+pub fn display_leaders(summary: &RunSummary) {
+    let Some(leaders) = summary.leaders.as_ref() else {
+        return;
+    };
+    if !summary.has_data {
+        return;
+    }
+
+    println!(
+        "\nBy slot leader ({} leaders, {} signatures outside the schedule; win rate / P50 delay ms)",
+        leaders.leaders.len(),
+        leaders.unmapped
+    );
+    println!("--------------------------------------------");
+
+    let mut table = Table::new();
+    table.load_preset(table_preset());
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    let mut header = vec![
+        "Leader".to_string(),
+        "Slots".to_string(),
+        "Signatures".to_string(),
+    ];
+    header.extend(leaders.endpoint_names.iter().cloned());
+    table.set_header(header);
+    for leader in leaders.leaders.iter().take(MAX_LEADERS_SHOWN) {
+        let mut row = vec![
+            leader.identity.clone(),
+            leader.slots.to_string(),
+            leader.signatures.to_string(),
+        ];
+        for stats in &leader.endpoints {
+            row.push(format!(
+                "{}% / {}",
+                format_percent(stats.firsts as f64 / leader.signatures as f64),
+                format_latency_value(stats.p50_delay_ms)
+            ));
+        }
+        table.add_row(row);
+    }
+    println!("{}", table);
+
+    if leaders.slow.is_empty() {
+        println!("No endpoint was consistently slow for any leader.");
+        return;
+    }
+
+    println!("\nConsistently slow leaders");
+    println!("--------------------------------------------");
+
+    let mut table = Table::new();
+    table.load_preset(table_preset());
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec![
+        "Leader",
+        "Endpoint",
+        "Signatures",
+        "P50 ms",
+        "Overall P50 ms",
+    ]);
+    for slow in &leaders.slow {
+        table.add_row(vec![
+            slow.identity.clone(),
+            slow.endpoint.clone(),
+            slow.signatures.to_string(),
+            format!("{:.2}", slow.p50_delay_ms),
+            format!("{:.2}", slow.baseline_p50_ms),
+        ]);
+    }
+    println!("{}", table);
+}
+
+/// How far each endpoint was ahead of the runner-up on the signatures it won.
+pub fn display_lead_times(summary: &RunSummary) {
+    if !summary.has_data {
+        return;
+    }
+
+    println!("\nLead time when first");
+    println!("--------------------------------------------");
+
+    let mut table = Table::new();
+    table.load_preset(table_preset());
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec![
+        "Endpoint",
+        "Wins vs runner-up",
+        "Lead P50 ms",
+        "Lead P95 ms",
+    ]);
+
+    let mut rows: Vec<&EndpointSummary> = summary.endpoints.iter().collect();
+    rows.sort_by(|a, b| {
+        b.lead_samples
+            .cmp(&a.lead_samples)
+            .then(a.name.cmp(&b.name))
+    });
+    for endpoint in rows {
+        table.add_row(vec![
+            endpoint.name.clone(),
+            endpoint.lead_samples.to_string(),
+            format_latency_value(endpoint.lead_p50_ms),
+            format_latency_value(endpoint.lead_p95_ms),
+        ]);
+    }
+    println!("{}", table);
+}
+
+/// Lag p99 above this means the client, not the feed, may be the bottleneck.
+const SATURATION_LAG_P99_MS: f64 = 5.0;
+
+pub fn display_client_overhead(summary: &RunSummary) {
+    if summary.runtimes.is_empty() {
+        return;
+    }
+
+    println!("\nClient overhead");
+    println!("--------------------------------------------");
+
+    let mut table = Table::new();
+    table.load_preset(table_preset());
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec![
+        "Endpoint",
+        "Runtime",
+        "Receive→decode µs p50/p99",
+        "Filter µs p50/p99",
+        "Record µs p50/p99",
+        "Loop lag ms p50/p99/max",
+        "Untimed frames",
+    ]);
+
+    let format_lag =
+        |lag: &LagSnapshot| format!("{:.2}/{:.2}/{:.2}", lag.p50_ms, lag.p99_ms, lag.max_ms);
+    let mut saturated = Vec::new();
+    for runtime in &summary.runtimes {
+        let lag = runtime.event_loop_lag.as_ref();
+        if lag.is_some_and(|lag| lag.p99_ms > SATURATION_LAG_P99_MS) {
+            saturated.push(runtime.name.as_str());
+        }
+
+        table.add_row(vec![
+            runtime.name.clone(),
+            runtime.placement.clone(),
+            format_stage(runtime.overhead.decode.as_ref()),
+            format_stage(runtime.overhead.filter.as_ref()),
+            format_stage(runtime.overhead.record.as_ref()),
+            lag.map(format_lag).unwrap_or_else(|| "—".to_string()),
+            runtime.overhead.untimed_frames.to_string(),
+        ]);
+    }
+    // One probe covers every endpoint on the shared runtime.
+    if let Some(lag) = summary.shared_runtime_lag.as_ref() {
+        if lag.p99_ms > SATURATION_LAG_P99_MS {
+            saturated.push("shared runtime");
+        }
+        table.add_row(vec![
+            "(all shared)".to_string(),
+            "shared".to_string(),
+            "—".to_string(),
+            "—".to_string(),
+            "—".to_string(),
+            format_lag(lag),
+            "—".to_string(),
+        ]);
+    }
+
+    println!("{}", table);
+
+    if !saturated.is_empty() {
+        println!(
+            "⚠ Event-loop lag p99 exceeded {:.0}ms for: {}. The client may have been saturated; consider discarding these results.",
+            SATURATION_LAG_P99_MS,
+            saturated.join(", ")
+        );
+    }
+}
+
+pub fn display_stream_setup(summary: &RunSummary) {
+    if summary.stream_setups.is_empty() {
+        return;
+    }
+
+    println!("\nStream setup");
+    println!("--------------------------------------------");
+
+    let mut table = Table::new();
+    table.load_preset(table_preset());
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec!["Endpoint", "Connected ms", "Subscribed ms"]);
+    for setup in &summary.stream_setups {
+        table.add_row(vec![
+            setup.name.clone(),
+            format_offset_ms(setup.connected_at),
+            format_offset_ms(setup.subscribed_at),
+        ]);
+    }
+    println!("{}", table);
+
+    match summary.counting_started_at {
+        Some(started) => println!(
+            "Counting started {:.0}ms after launch",
+            started.as_secs_f64() * 1_000.0
+        ),
+        None => println!("Counting never started; not every endpoint subscribed"),
+    }
+}
+
+fn format_offset_ms(offset: Option<Duration>) -> String {
+    offset
+        .map(|offset| format!("{:.0}", offset.as_secs_f64() * 1_000.0))
+        .unwrap_or_else(|| "—".to_string())
+}
+
+fn format_stage(stage: Option<&StageSnapshot>) -> String {
+    stage
+        .map(|stage| format!("{:.1}/{:.1}", stage.p50_us, stage.p99_us))
+        .unwrap_or_else(|| "—".to_string())
+}
+
 pub fn build_metrics_report(summary: &RunSummary) -> Value {
     let mut per_endpoint = Map::new();
     for endpoint in &summary.endpoints {
+        let latency_percentiles: Map<String, Value> = summary
+            .percentiles
+            .iter()
+            .zip(&endpoint.delay_percentiles)
+            .map(|(quantile, value)| (percentile_label(*quantile).to_lowercase(), json!(value)))
+            .collect();
+        let percentile_cis: Map<String, Value> = summary
+            .percentiles
+            .iter()
+            .zip(&endpoint.delay_percentile_cis)
+            .map(|(quantile, interval)| {
+                (
+                    percentile_label(*quantile).to_lowercase(),
+                    interval_payload(*interval),
+                )
+            })
+            .collect();
         let payload = json!({
             "first_detection_rate": endpoint.first_share,
+            "first_detection_rate_ci": interval_payload(endpoint.first_share_ci),
             "p50_latency_ms": endpoint.p50_delay_ms,
             "p95_latency_ms": endpoint.p95_delay_ms,
             "p99_latency_ms": endpoint.p99_delay_ms,
+            "mean_latency_ms": endpoint.mean_delay_ms,
+            "stddev_latency_ms": endpoint.stddev_delay_ms,
+            "max_latency_ms": endpoint.max_delay_ms,
+            "latency_percentiles_ms": latency_percentiles,
+            "latency_percentiles_ci_ms": percentile_cis,
+            "latency_histogram_us": endpoint.delay_histogram.as_ref().and_then(encode_histogram),
             "observations": endpoint.valid_transactions,
             "first_detections": endpoint.first_detections,
             "backfill_transactions": endpoint.backfill_transactions,
+            "coverage": endpoint.coverage,
+            "delivered_signatures": endpoint.delivered_signatures,
+            "missed_signatures": endpoint.missed_signatures,
+            "late_signatures": endpoint.late_signatures,
+            "exclusive_signatures": endpoint.exclusive_signatures,
+            "lead_time_ms": {
+                "samples": endpoint.lead_samples,
+                "p50": endpoint.lead_p50_ms,
+                "p95": endpoint.lead_p95_ms,
+            },
         });
         per_endpoint.insert(endpoint.name.clone(), payload);
     }
 
+    let lag_payload = |lag: Option<&LagSnapshot>| {
+        json!({
+            "samples": lag.map(|l| l.samples),
+            "mean_ms": lag.map(|l| l.mean_ms),
+            "p50_ms": lag.map(|l| l.p50_ms),
+            "p99_ms": lag.map(|l| l.p99_ms),
+            "max_ms": lag.map(|l| l.max_ms),
+        })
+    };
+    let mut client_overhead = Map::new();
+    for runtime in &summary.runtimes {
+        let payload = json!({
+            "runtime": runtime.placement,
+            "event_loop_lag": lag_payload(runtime.event_loop_lag.as_ref()),
+            "decode": stage_payload(runtime.overhead.decode.as_ref()),
+            "filter": stage_payload(runtime.overhead.filter.as_ref()),
+            "record": stage_payload(runtime.overhead.record.as_ref()),
+            "untimed_frames": runtime.overhead.untimed_frames,
+        });
+        client_overhead.insert(runtime.name.clone(), payload);
+    }
+
+    let mut stream_setup = Map::new();
+    for setup in &summary.stream_setups {
+        stream_setup.insert(
+            setup.name.clone(),
+            json!({
+                "connected_ms": setup.connected_at.map(|at| at.as_secs_f64() * 1_000.0),
+                "subscribed_ms": setup.subscribed_at.map(|at| at.as_secs_f64() * 1_000.0),
+            }),
+        );
+    }
+
+    let mut head_to_head_report = Map::new();
+    for row in &summary.endpoints {
+        let mut columns = Map::new();
+        for column in &summary.endpoints {
+            let Some(test) = pair_test(summary, &row.name, &column.name) else {
+                continue;
+            };
+            let Some((win_rate, median_delta_ms)) = test.seen_from(&row.name) else {
+                continue;
+            };
+            columns.insert(
+                column.name.clone(),
+                json!({
+                    "shared_signatures": test.shared(),
+                    "win_rate": win_rate.is_finite().then_some(win_rate),
+                    "median_delta_ms": median_delta_ms,
+                }),
+            );
+        }
+        head_to_head_report.insert(row.name.clone(), Value::Object(columns));
+    }
+
+    let incidents: Vec<Value> = summary
+        .timeline
+        .incidents
+        .iter()
+        .map(|incident| {
+            json!({
+                "endpoint": incident.endpoint,
+                "start_secs": incident.start_secs,
+                "end_secs": incident.end_secs,
+                "baseline_p95_ms": incident.baseline_p95_ms,
+                "peak_p95_ms": incident.peak_p95_ms,
+            })
+        })
+        .collect();
+
+    let slot_endpoints: Map<String, Value> = summary
+        .slots
+        .endpoint_names
+        .iter()
+        .zip(&summary.slots.endpoints)
+        .map(|(name, stats)| {
+            (
+                name.clone(),
+                json!({
+                    "slots_led": stats.slots_led,
+                    "reported": stats.reported,
+                    "mismatches": stats.mismatches,
+                }),
+            )
+        })
+        .collect();
+    let slot_positions: Vec<Value> = summary
+        .slots
+        .positions
+        .iter()
+        .map(|band| {
+            let endpoints: Map<String, Value> = summary
+                .slots
+                .endpoint_names
+                .iter()
+                .zip(&band.endpoints)
+                .map(|(name, stats)| {
+                    (
+                        name.clone(),
+                        json!({
+                            "firsts": stats.firsts,
+                            "p50_delay_ms": stats.p50_delay_ms,
+                        }),
+                    )
+                })
+                .collect();
+            json!({
+                "start_ms": band.start_ms,
+                "end_ms": band.end_ms,
+                "signatures": band.signatures,
+                "endpoints": endpoints,
+            })
+        })
+        .collect();
+
+    let groups: Vec<Value> = summary
+        .groups
+        .iter()
+        .map(|group| {
+            json!({
+                "group_by": group.group_by.label(),
+                "key": group.key,
+                "metrics": build_metrics_report(&group.summary),
+            })
+        })
+        .collect();
+
+    let class_tests: Vec<Value> = summary
+        .class_tests
+        .iter()
+        .map(|class_test| {
+            json!({
+                "group_by": class_test.group_by.label(),
+                "chi_square": class_test.test.statistic,
+                "degrees_of_freedom": class_test.test.degrees_of_freedom,
+                "p_value": class_test.test.p_value,
+            })
+        })
+        .collect();
+
+    let leaders = summary.leaders.as_ref().map(|breakdown| {
+        let rows: Vec<Value> = breakdown
+            .leaders
+            .iter()
+            .map(|leader| {
+                let endpoints: Map<String, Value> = breakdown
+                    .endpoint_names
+                    .iter()
+                    .zip(&leader.endpoints)
+                    .map(|(name, stats)| {
+                        (
+                            name.clone(),
+                            json!({
+                                "firsts": stats.firsts,
+                                "delivered": stats.delivered,
+                                "p50_delay_ms": stats.p50_delay_ms,
+                            }),
+                        )
+                    })
+                    .collect();
+                json!({
+                    "identity": leader.identity,
+                    "slots": leader.slots,
+                    "signatures": leader.signatures,
+                    "endpoints": endpoints,
+                })
+            })
+            .collect();
+        let slow: Vec<Value> = breakdown
+            .slow
+            .iter()
+            .map(|slow| {
+                json!({
+                    "identity": slow.identity,
+                    "endpoint": slow.endpoint,
+                    "signatures": slow.signatures,
+                    "p50_delay_ms": slow.p50_delay_ms,
+                    "baseline_p50_ms": slow.baseline_p50_ms,
+                })
+            })
+            .collect();
+        json!({
+            "unmapped_signatures": breakdown.unmapped,
+            "leaders": rows,
+            "slow": slow,
+        })
+    });
+
+    let pairwise: Vec<Value> = summary
+        .pairwise
+        .iter()
+        .map(|test| {
+            json!({
+                "endpoints": [test.lhs, test.rhs],
+                "faster_counts": [test.lhs_faster, test.rhs_faster],
+                "ties": test.ties,
+                "p_value": test.p_value,
+                "winner": test.winner(SIGNIFICANCE_LEVEL),
+            })
+        })
+        .collect();
+
     json!({
         "total_signatures": summary.total_signatures,
         "backfill_signatures": summary.backfill_signatures,
-        "per_endpoint": per_endpoint
+        "union_signatures": summary.union_signatures,
+        "counting_started_ms": summary
+            .counting_started_at
+            .map(|at| at.as_secs_f64() * 1_000.0),
+        "fastest_endpoint": summary.fastest_endpoint,
+        "pairwise_sign_tests": pairwise,
+        "head_to_head": head_to_head_report,
+        "timeline": {
+            "bucket_secs": summary.timeline.bucket_secs,
+            "buckets": summary.timeline.buckets.len(),
+            "incidents": incidents,
+        },
+        "slots": {
+            "slots": summary.slots.slots.len(),
+            "signatures_with_slot": summary.slots.signatures_with_slot,
+            "disagreements": summary.slots.disagreements,
+            "per_endpoint": slot_endpoints,
+            "by_position": slot_positions,
+        },
+        "groups": groups,
+        "class_tests": class_tests,
+        "leaders": leaders,
+        "per_endpoint": per_endpoint,
+        "client_overhead": client_overhead,
+        "shared_runtime_lag": summary.shared_runtime_lag.as_ref().map(|lag| lag_payload(Some(lag))),
+        "stream_setup": stream_setup
     })
 }
 
+fn interval_payload(interval: Option<Interval>) -> Value {
+    match interval {
+        Some(interval) => json!([interval.low, interval.high]),
+        None => Value::Null,
+    }
+}
+
+fn stage_payload(stage: Option<&StageSnapshot>) -> Value {
+    match stage {
+        Some(stage) => json!({
+            "samples": stage.samples,
+            "p50_us": stage.p50_us,
+            "p99_us": stage.p99_us,
+            "max_us": stage.max_us,
+        }),
+        None => Value::Null,
+    }
+}
+
 fn diff_ms(tx: &TransactionData, first_tx: &TransactionData) -> f64 {
     let delta: Duration = tx
-        .elapsed_since_start
-        .saturating_sub(first_tx.elapsed_since_start);
+        .arrival_elapsed()
+        .saturating_sub(first_tx.arrival_elapsed());
     delta.as_secs_f64() * 1_000.0
 }
 
 fn build_summary(
     endpoint: String,
     stats: EndpointStats,
+    intervals: EndpointIntervals,
     total_signatures: usize,
+    union_signatures: usize,
+    percentiles: &[f64],
 ) -> EndpointSummary {
     let mut summary = EndpointSummary {
         name: endpoint,
         valid_transactions: stats.total_observations,
         first_detections: stats.first_detections,
         backfill_transactions: stats.backfill_transactions,
+        delivered_signatures: stats.delivered,
+        missed_signatures: union_signatures
+            .saturating_sub(stats.delivered + stats.late + stats.pending),
+        late_signatures: stats.late,
+        exclusive_signatures: stats.exclusive,
+        delay_percentiles: vec![None; percentiles.len()],
+        first_share_ci: intervals.first_share,
+        delay_percentile_cis: intervals.delay_percentiles,
         ..Default::default()
     };
 
     if total_signatures > 0 {
         summary.first_share = stats.first_detections as f64 / total_signatures as f64;
     }
+    if union_signatures > 0 {
+        summary.coverage = stats.delivered as f64 / union_signatures as f64;
+    }
 
     if !stats.delays_ms.is_empty() {
         let mut sorted = stats.delays_ms.clone();
@@ -250,22 +1710,78 @@ fn build_summary(
         summary.p50_delay_ms = Some(percentile(&sorted, 0.5));
         summary.p95_delay_ms = Some(percentile(&sorted, 0.95));
         summary.p99_delay_ms = Some(percentile(&sorted, 0.99));
+        summary.delay_percentiles = percentiles
+            .iter()
+            .map(|quantile| Some(percentile(&sorted, *quantile)))
+            .collect();
+
+        let count = sorted.len() as f64;
+        let mean = sorted.iter().sum::<f64>() / count;
+        let variance = sorted
+            .iter()
+            .map(|delay| (delay - mean).powi(2))
+            .sum::<f64>()
+            / count;
+        summary.mean_delay_ms = Some(mean);
+        summary.stddev_delay_ms = Some(variance.sqrt());
+        summary.max_delay_ms = sorted.last().copied();
+
+        let mut histogram = Histogram::<u64>::new_with_bounds(1, DELAY_HISTOGRAM_MAX_US, 3)
+            .expect("valid delay histogram bounds");
+        for delay_ms in &sorted {
+            let micros = (delay_ms * 1_000.0).round() as u64;
+            histogram.saturating_record(micros.min(DELAY_HISTOGRAM_MAX_US));
+        }
+        summary.delay_histogram = Some(histogram);
+        summary.delays_ms = sorted;
+    }
+
+    if !stats.lead_times_ms.is_empty() {
+        let mut leads = stats.lead_times_ms;
+        leads.sort_by(f64::total_cmp);
+        summary.lead_samples = leads.len();
+        summary.lead_p50_ms = Some(percentile(&leads, 0.5));
+        summary.lead_p95_ms = Some(percentile(&leads, 0.95));
     }
 
     summary
 }
 
-fn format_latency_value(value: Option<f64>, is_fastest: bool) -> String {
-    if is_fastest {
-        "-".to_string()
-    } else {
-        value
-            .map(|v| format!("{:.2}", v))
-            .unwrap_or_else(|| "—".to_string())
+/// Base64 of the compressed HdrHistogram V2 encoding, as used in
+/// HdrHistogram interval logs.
+fn encode_histogram(histogram: &Histogram<u64>) -> Option<String> {
+    let mut bytes = Vec::new();
+    V2DeflateSerializer::new()
+        .serialize(histogram, &mut bytes)
+        .ok()?;
+    Some(base64::engine::general_purpose::STANDARD.encode(bytes))
+}
+
+/// Column label for a quantile, e.g. `0.5` -> `P50`, `0.999` -> `P99.9`,
+/// `1.0` -> `Max`.
+pub fn percentile_label(quantile: f64) -> String {
+    if quantile >= 1.0 {
+        return "Max".to_string();
     }
+    let percent = format!("{:.3}", quantile * 100.0);
+    let percent = percent.trim_end_matches('0').trim_end_matches('.');
+    format!("P{}", percent)
 }
 
-fn compare_latency(lhs: &EndpointSummary, rhs: &EndpointSummary) -> Ordering {
+fn format_latency_value(value: Option<f64>) -> String {
+    value
+        .map(|v| format!("{:.2}", v))
+        .unwrap_or_else(|| "—".to_string())
+}
+
+fn format_mean_stddev(summary: &EndpointSummary) -> String {
+    match (summary.mean_delay_ms, summary.stddev_delay_ms) {
+        (Some(mean), Some(stddev)) => format!("{:.2} ± {:.2}", mean, stddev),
+        _ => "—".to_string(),
+    }
+}
+
+pub fn compare_latency(lhs: &EndpointSummary, rhs: &EndpointSummary) -> Ordering {
     match (lhs.p50_delay_ms, rhs.p50_delay_ms) {
         (Some(l), Some(r)) => l
             .partial_cmp(&r)
@@ -284,3 +1800,153 @@ fn format_percent(value: f64) -> String {
         "—".to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TransactionTags;
+
+    const START_SECS: f64 = 1_700_000_000.0;
+
+    fn observation(at_ms: u64) -> TransactionData {
+        let elapsed = Duration::from_millis(at_ms);
+        TransactionData {
+            wallclock_secs: START_SECS + elapsed.as_secs_f64(),
+            elapsed_since_start: elapsed,
+            start_wallclock_secs: START_SECS,
+            receive_wallclock_secs: None,
+            slot: None,
+            tags: TransactionTags::default(),
+        }
+    }
+
+    fn endpoint<'a>(summary: &'a RunSummary, name: &str) -> &'a EndpointSummary {
+        summary
+            .endpoints
+            .iter()
+            .find(|endpoint| endpoint.name == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn late_threshold_classifies_late_and_missed_deliveries() {
+        let comparator = Comparator::new();
+        let deliveries: [(&str, &str, u64); 6] = [
+            // Every endpoint delivered it, but `c` after the threshold.
+            ("all", "a", 100),
+            ("all", "b", 110),
+            ("all", "c", 200),
+            // `c` never delivered it and the threshold has passed.
+            ("expired", "a", 300),
+            ("expired", "b", 305),
+            // Still inside the threshold when observation ended.
+            ("pending", "a", 9_990),
+        ];
+        for (signature, name, at_ms) in deliveries {
+            comparator.add_batch(
+                name,
+                HashMap::from([(signature.to_string(), observation(at_ms))]),
+            );
+        }
+
+        let names = ["a", "b", "c"].map(String::from);
+        let options = AnalysisOptions {
+            late_threshold_ms: Some(50.0),
+            observed_until: Some(Duration::from_secs(10)),
+            ..Default::default()
+        };
+        let summary = compute_run_summary(&comparator, &names, &options);
+
+        assert_eq!(summary.union_signatures, 3);
+        assert_eq!(summary.total_signatures, 2);
+
+        let a = endpoint(&summary, "a");
+        assert_eq!((a.late_signatures, a.missed_signatures), (0, 0));
+        let b = endpoint(&summary, "b");
+        // Neither missed `pending` yet, as the threshold had not passed.
+        assert_eq!((b.late_signatures, b.missed_signatures), (0, 0));
+        let c = endpoint(&summary, "c");
+        assert_eq!((c.late_signatures, c.missed_signatures), (1, 1));
+        assert_eq!(c.valid_transactions, 0);
+    }
+
+    /// `a` and `b` both deliver `sig0`..`sig4` at 0.5 s steps from 0.5 s;
+    /// `b` never delivers `sig5` at 1.2 s.
+    fn warmup_comparator() -> Comparator {
+        let comparator = Comparator::new();
+        for index in 0..5u64 {
+            for name in ["a", "b"] {
+                comparator.add_batch(
+                    name,
+                    HashMap::from([(format!("sig{index}"), observation(500 + index * 500))]),
+                );
+            }
+        }
+        comparator.add_batch(
+            "a",
+            HashMap::from([("sig5".to_string(), observation(1_200))]),
+        );
+        comparator
+    }
+
+    #[test]
+    fn warmup_cutoff_by_time_starts_at_connection() {
+        let comparator = warmup_comparator();
+        let names = ["a", "b"].map(String::from);
+        let connected_at = Duration::from_secs(1);
+
+        let cutoff = |secs| warmup_cutoff(&comparator, &names, connected_at, secs, None);
+        assert_eq!(cutoff(None), None);
+        assert_eq!(cutoff(Some(2.0)), Some(3.0));
+        assert_eq!(cutoff(Some(-1.0)), Some(1.0));
+    }
+
+    #[test]
+    fn warmup_cutoff_by_count_skips_signatures_seen_by_every_endpoint() {
+        let comparator = warmup_comparator();
+        let names = ["a", "b"].map(String::from);
+        let connected_at = Duration::from_secs(1);
+        let cutoff = |count| warmup_cutoff(&comparator, &names, connected_at, None, count);
+
+        // `sig0` predates the connection and `sig5` is incomplete, so the
+        // first two counted are `sig1` and `sig2`, and `sig3` ends warmup.
+        assert_eq!(cutoff(Some(2)), Some(2.0));
+        assert_eq!(cutoff(Some(0)), None);
+        assert_eq!(cutoff(Some(4)), Some(f64::INFINITY));
+
+        // With both set, the later cutoff wins.
+        let both = |secs| warmup_cutoff(&comparator, &names, connected_at, Some(secs), Some(2));
+        assert_eq!(both(0.5), Some(2.0));
+        assert_eq!(both(1.5), Some(2.5));
+    }
+
+    #[test]
+    fn exclusive_signatures_count_late_deliveries_from_others() {
+        let comparator = Comparator::new();
+        let deliveries: [(&str, &str, u64); 3] = [
+            ("alone", "a", 100),
+            // `b` delivered it, if only after the threshold.
+            ("late_elsewhere", "a", 200),
+            ("late_elsewhere", "b", 400),
+        ];
+        for (signature, name, at_ms) in deliveries {
+            comparator.add_batch(
+                name,
+                HashMap::from([(signature.to_string(), observation(at_ms))]),
+            );
+        }
+
+        let names = ["a", "b"].map(String::from);
+        let options = AnalysisOptions {
+            late_threshold_ms: Some(50.0),
+            observed_until: Some(Duration::from_secs(10)),
+            ..Default::default()
+        };
+        let summary = compute_run_summary(&comparator, &names, &options);
+
+        let a = endpoint(&summary, "a");
+        assert_eq!((a.delivered_signatures, a.exclusive_signatures), (2, 1));
+        let b = endpoint(&summary, "b");
+        assert_eq!((b.late_signatures, b.missed_signatures), (1, 1));
+    }
+}
@@ -0,0 +1,269 @@
+use std::{env, path::PathBuf};
+
+use crate::{analysis::GroupBy, leaders::LeaderSource, report::ReportFormat};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Benchmark,
+    MockServer,
+    Replay,
+    Analyze,
+    Compare,
+}
+
+impl Command {
+    fn from_arg(arg: &str) -> Option<Self> {
+        match arg {
+            "mock-server" => Some(Command::MockServer),
+            "replay" => Some(Command::Replay),
+            "analyze" => Some(Command::Analyze),
+            "compare" => Some(Command::Compare),
+            _ => None,
+        }
+    }
+
+    fn expected_inputs(&self) -> usize {
+        match self {
+            Command::Benchmark | Command::MockServer => 0,
+            Command::Replay | Command::Analyze => 1,
+            Command::Compare => 2,
+        }
+    }
+}
+
+pub struct CliArgs {
+    pub command: Command,
+    pub config_path: Option<String>,
+    pub disable_streaming: bool,
+    pub record_dir: Option<PathBuf>,
+    pub observations_out: Option<PathBuf>,
+    /// Where to write the run metrics (or the run diff for `compare`) as JSON.
+    pub metrics_out: Option<PathBuf>,
+    pub report_format: Option<ReportFormat>,
+    /// Where to write the report; stdout when only a format is given.
+    pub report_out: Option<PathBuf>,
+    pub replay_fast: bool,
+    /// Positional paths for subcommands (capture directory, observation file).
+    pub inputs: Vec<PathBuf>,
+    pub endpoints: Option<Vec<String>>,
+    /// Windows to leave out of the analysis, in seconds since run start.
+    pub excluded_ranges: Vec<(f64, f64)>,
+    /// Percentiles as fractions in `(0, 1]`.
+    pub percentiles: Option<Vec<f64>>,
+    pub late_threshold_ms: Option<f64>,
+    /// Width of the time-series buckets, in seconds.
+    pub bucket_secs: Option<f64>,
+    /// Where to write the per-bucket time series as CSV.
+    pub timeseries_out: Option<PathBuf>,
+    /// Where to write per-slot wins as CSV.
+    pub slots_out: Option<PathBuf>,
+    pub group_by: Vec<GroupBy>,
+    /// Overrides the config's leader schedule source.
+    pub leader_schedule: Option<LeaderSource>,
+}
+
+impl CliArgs {
+    pub fn parse() -> Self {
+        let mut args = env::args().skip(1);
+        let mut parsed = CliArgs {
+            command: Command::Benchmark,
+            config_path: None,
+            disable_streaming: false,
+            record_dir: None,
+            observations_out: None,
+            metrics_out: None,
+            report_format: None,
+            report_out: None,
+            replay_fast: false,
+            inputs: Vec::new(),
+            endpoints: None,
+            excluded_ranges: Vec::new(),
+            percentiles: None,
+            late_threshold_ms: None,
+            bucket_secs: None,
+            timeseries_out: None,
+            slots_out: None,
+            group_by: Vec::new(),
+            leader_schedule: None,
+        };
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--config" => {
+                    parsed.config_path = Some(required_value(&mut args, "--config"));
+                }
+                "--private" => {
+                    parsed.disable_streaming = true;
+                }
+                "--record" => {
+                    parsed.record_dir = Some(PathBuf::from(required_value(&mut args, "--record")));
+                }
+                "--observations-out" => {
+                    let value = required_value(&mut args, "--observations-out");
+                    parsed.observations_out = Some(PathBuf::from(value));
+                }
+                "--metrics-out" => {
+                    let value = required_value(&mut args, "--metrics-out");
+                    parsed.metrics_out = Some(PathBuf::from(value));
+                }
+                "--report-format" => {
+                    let value = required_value(&mut args, "--report-format");
+                    let format = value
+                        .parse()
+                        .unwrap_or_else(|err: anyhow::Error| fail(&err.to_string()));
+                    parsed.report_format = Some(format);
+                }
+                "--report-out" => {
+                    let value = required_value(&mut args, "--report-out");
+                    parsed.report_out = Some(PathBuf::from(value));
+                }
+                "--fast" => {
+                    parsed.replay_fast = true;
+                }
+                "--endpoints" => {
+                    let value = required_value(&mut args, "--endpoints");
+                    parsed.endpoints = Some(
+                        value
+                            .split(',')
+                            .map(|name| name.trim().to_string())
+                            .filter(|name| !name.is_empty())
+                            .collect(),
+                    );
+                }
+                "--exclude" => {
+                    let value = required_value(&mut args, "--exclude");
+                    let range = parse_range(&value)
+                        .unwrap_or_else(|| fail(&format!("Invalid --exclude range: {}", value)));
+                    parsed.excluded_ranges.push(range);
+                }
+                "--percentiles" => {
+                    let value = required_value(&mut args, "--percentiles");
+                    let percentiles = parse_percentiles(&value)
+                        .unwrap_or_else(|| fail(&format!("Invalid --percentiles list: {}", value)));
+                    parsed.percentiles = Some(percentiles);
+                }
+                "--late-threshold-ms" => {
+                    let value = required_value(&mut args, "--late-threshold-ms");
+                    let threshold = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|ms| ms.is_finite() && *ms >= 0.0)
+                        .unwrap_or_else(|| {
+                            fail(&format!("Invalid --late-threshold-ms value: {}", value))
+                        });
+                    parsed.late_threshold_ms = Some(threshold);
+                }
+                "--bucket-secs" => {
+                    let value = required_value(&mut args, "--bucket-secs");
+                    let bucket_secs = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|secs| secs.is_finite() && *secs > 0.0)
+                        .unwrap_or_else(|| {
+                            fail(&format!("Invalid --bucket-secs value: {}", value))
+                        });
+                    parsed.bucket_secs = Some(bucket_secs);
+                }
+                "--timeseries-out" => {
+                    let value = required_value(&mut args, "--timeseries-out");
+                    parsed.timeseries_out = Some(PathBuf::from(value));
+                }
+                "--group-by" => {
+                    let value = required_value(&mut args, "--group-by");
+                    parsed.group_by = value
+                        .split(',')
+                        .map(|item| GroupBy::from_arg(item.trim()))
+                        .collect::<Option<Vec<_>>>()
+                        .unwrap_or_else(|| fail(&format!("Invalid --group-by value: {}", value)));
+                }
+                "--slots-out" => {
+                    let value = required_value(&mut args, "--slots-out");
+                    parsed.slots_out = Some(PathBuf::from(value));
+                }
+                "--leader-schedule" => {
+                    let value = required_value(&mut args, "--leader-schedule");
+                    parsed.leader_schedule = Some(LeaderSource::from_arg(&value));
+                }
+                "--help" | "-h" => {
+                    print_usage();
+                    std::process::exit(0);
+                }
+                other => match Command::from_arg(other) {
+                    Some(command) if parsed.command == Command::Benchmark => {
+                        parsed.command = command;
+                    }
+                    _ if !other.starts_with('-')
+                        && parsed.inputs.len() < parsed.command.expected_inputs() =>
+                    {
+                        parsed.inputs.push(PathBuf::from(other));
+                    }
+                    _ => fail(&format!("Unknown argument: {}", other)),
+                },
+            }
+        }
+
+        if parsed.inputs.len() != parsed.command.expected_inputs() {
+            fail("Missing input path");
+        }
+
+        parsed
+    }
+}
+
+fn required_value(args: &mut impl Iterator<Item = String>, flag: &str) -> String {
+    args.next()
+        .unwrap_or_else(|| fail(&format!("Missing value for {}", flag)))
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    print_usage();
+    std::process::exit(1);
+}
+
+/// Parses `START..END` in seconds since run start.
+fn parse_range(value: &str) -> Option<(f64, f64)> {
+    let (start, end) = value.split_once("..")?;
+    let start: f64 = start.trim().parse().ok()?;
+    let end: f64 = end.trim().parse().ok()?;
+    (start.is_finite() && end.is_finite() && start < end).then_some((start, end))
+}
+
+/// Parses a comma-separated list of percentiles such as `50,95,99.9,max`.
+fn parse_percentiles(value: &str) -> Option<Vec<f64>> {
+    let percentiles = value
+        .split(',')
+        .map(|item| match item.trim() {
+            "max" => Some(1.0),
+            item => {
+                let percent: f64 = item.parse().ok()?;
+                (percent > 0.0 && percent <= 100.0).then_some(percent / 100.0)
+            }
+        })
+        .collect::<Option<Vec<_>>>()?;
+    (!percentiles.is_empty()).then_some(percentiles)
+}
+
+pub fn print_usage() {
+    eprintln!(
+        "Usage: geyserbench [--config <PATH>] [--private] [--record <DIR>] [--observations-out <PATH>]"
+    );
+    eprintln!(
+        "       geyserbench replay <DIR> [--config <PATH>] [--fast] [--observations-out <PATH>]"
+    );
+    eprintln!(
+        "       geyserbench analyze <FILE> [--endpoints <A,B,..>] [--exclude <START..END>]... [--percentiles <P,..|max>] [--late-threshold-ms <MS>]"
+    );
+    eprintln!("       geyserbench compare <RUN_A> <RUN_B> [--metrics-out <PATH>]");
+    eprintln!("       geyserbench mock-server [--config <PATH>]");
+    eprintln!();
+    eprintln!("Runs and analyses also accept --metrics-out <PATH> to save metrics as JSON,");
+    eprintln!("--report-format <json|csv|markdown|html> [--report-out <PATH>] for a report");
+    eprintln!("(json is an alias for --metrics-out),");
+    eprintln!("--percentiles <P,..|max> to choose the delay percentiles,");
+    eprintln!("--timeseries-out <PATH> [--bucket-secs <S>] to export latency over time,");
+    eprintln!("--slots-out <PATH> to export wins per slot,");
+    eprintln!("--group-by <account,program,status,cu-price,tip> to break results down");
+    eprintln!("per account, program, execution status, compute-unit price or Jito tip,");
+    eprintln!("and --leader-schedule <FILE|RPC_URL> to break results down per slot leader.");
+}
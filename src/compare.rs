@@ -0,0 +1,277 @@
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result, anyhow};
+use comfy_table::{Cell, Color, ContentArrangement, Table};
+use serde_json::{Map, Value, json};
+
+use crate::{
+    analysis::{self, AnalysisOptions},
+    observations::{self, ObservationFormat},
+};
+
+/// Per-endpoint metrics compared between runs and whether larger is better.
+const COMPARED_METRICS: [(&str, &str, bool); 6] = [
+    ("first_detection_rate", "First %", true),
+    ("coverage", "Coverage %", true),
+    ("p50_latency_ms", "P50 ms", false),
+    ("p95_latency_ms", "P95 ms", false),
+    ("p99_latency_ms", "P99 ms", false),
+    ("observations", "Valid Tx", true),
+];
+
+/// Deltas smaller than this are float noise from re-deriving metrics.
+const DELTA_TOLERANCE: f64 = 1e-6;
+
+#[derive(Debug, Clone)]
+pub struct MetricDelta {
+    pub key: &'static str,
+    pub before: Option<f64>,
+    pub after: Option<f64>,
+    pub higher_is_better: bool,
+}
+
+impl MetricDelta {
+    pub fn delta(&self) -> Option<f64> {
+        Some(self.after? - self.before?)
+    }
+
+    fn significant_delta(&self) -> Option<f64> {
+        self.delta().filter(|delta| delta.abs() > DELTA_TOLERANCE)
+    }
+
+    pub fn is_regression(&self) -> bool {
+        match self.significant_delta() {
+            Some(delta) if self.higher_is_better => delta < 0.0,
+            Some(delta) => delta > 0.0,
+            None => false,
+        }
+    }
+
+    pub fn is_improvement(&self) -> bool {
+        self.significant_delta().is_some() && !self.is_regression()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EndpointDiff {
+    pub name: String,
+    pub metrics: Vec<MetricDelta>,
+}
+
+/// Loads run metrics from a saved `--metrics-out` JSON file, or recomputes them
+/// from an observations file.
+pub fn load_run_metrics(path: &Path) -> Result<Value> {
+    if path.extension().and_then(|ext| ext.to_str()) == Some("json") {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read metrics {}", path.display()))?;
+        return serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse metrics {}", path.display()));
+    }
+
+    ObservationFormat::from_path(path)?;
+    let records = observations::read_observations(path)?;
+    let (comparator, endpoint_names) = observations::load_comparator(records);
+    let mut options = AnalysisOptions::default();
+    if let Some(context) = observations::read_run_context(path)? {
+        context.apply(&mut options);
+    }
+    let summary = analysis::compute_run_summary(&comparator, &endpoint_names, &options);
+    Ok(analysis::build_metrics_report(&summary))
+}
+
+pub fn diff_runs(before: &Value, after: &Value) -> Result<Vec<EndpointDiff>> {
+    let before = per_endpoint(before)?;
+    let after = per_endpoint(after)?;
+
+    let mut names: Vec<&String> = before.keys().chain(after.keys()).collect();
+    names.sort();
+    names.dedup();
+
+    Ok(names
+        .into_iter()
+        .map(|name| EndpointDiff {
+            name: name.clone(),
+            metrics: COMPARED_METRICS
+                .iter()
+                .map(|(key, _, higher_is_better)| MetricDelta {
+                    key,
+                    before: before.get(name).and_then(|metrics| metrics[*key].as_f64()),
+                    after: after.get(name).and_then(|metrics| metrics[*key].as_f64()),
+                    higher_is_better: *higher_is_better,
+                })
+                .collect(),
+        })
+        .collect())
+}
+
+fn per_endpoint(metrics: &Value) -> Result<&Map<String, Value>> {
+    metrics["per_endpoint"]
+        .as_object()
+        .ok_or_else(|| anyhow!("metrics report is missing per_endpoint"))
+}
+
+pub fn display_run_diff(diffs: &[EndpointDiff], before_label: &str, after_label: &str) {
+    println!("\nRun comparison: {} → {}", before_label, after_label);
+    println!("--------------------------------------------");
+
+    if diffs.is_empty() {
+        println!("No endpoints to compare");
+        return;
+    }
+
+    let mut table = Table::new();
+    table.load_preset(analysis::table_preset());
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    let mut header = vec!["Endpoint"];
+    header.extend(COMPARED_METRICS.iter().map(|(_, label, _)| *label));
+    table.set_header(header);
+
+    let mut regressions = Vec::new();
+    for diff in diffs {
+        let mut row = vec![Cell::new(&diff.name)];
+        for metric in &diff.metrics {
+            let cell = Cell::new(format_delta(metric));
+            let cell = if metric.is_regression() {
+                regressions.push(format!("{} {}", diff.name, metric_label(metric.key)));
+                cell.fg(Color::Red)
+            } else if metric.is_improvement() {
+                cell.fg(Color::Green)
+            } else {
+                cell
+            };
+            row.push(cell);
+        }
+        table.add_row(row);
+    }
+
+    println!("{}", table);
+
+    if !regressions.is_empty() {
+        println!("⚠ Regressions: {}", regressions.join(", "));
+    }
+}
+
+fn metric_label(key: &str) -> &'static str {
+    COMPARED_METRICS
+        .iter()
+        .find(|(candidate, _, _)| *candidate == key)
+        .map(|(_, label, _)| *label)
+        .unwrap_or("")
+}
+
+fn format_delta(metric: &MetricDelta) -> String {
+    let format_value = |value: Option<f64>| match (value, metric.key) {
+        (Some(value), "first_detection_rate" | "coverage") => format!("{:.2}", value * 100.0),
+        (Some(value), "observations") => format!("{:.0}", value),
+        (Some(value), _) => format!("{:.2}", value),
+        (None, _) => "—".to_string(),
+    };
+
+    let delta = match (metric.delta(), metric.key) {
+        (Some(delta), "first_detection_rate" | "coverage") => {
+            format!(" ({:+.2})", delta * 100.0)
+        }
+        (Some(delta), "observations") => format!(" ({:+.0})", delta),
+        (Some(delta), _) => format!(" ({:+.2})", delta),
+        (None, _) => String::new(),
+    };
+
+    format!(
+        "{} → {}{}",
+        format_value(metric.before),
+        format_value(metric.after),
+        delta
+    )
+}
+
+pub fn build_diff_report(diffs: &[EndpointDiff], before_label: &str, after_label: &str) -> Value {
+    let mut per_endpoint = Map::new();
+    for diff in diffs {
+        let mut metrics = Map::new();
+        for metric in &diff.metrics {
+            metrics.insert(
+                metric.key.to_string(),
+                json!({
+                    "before": metric.before,
+                    "after": metric.after,
+                    "delta": metric.delta(),
+                    "regression": metric.is_regression(),
+                }),
+            );
+        }
+        per_endpoint.insert(diff.name.clone(), Value::Object(metrics));
+    }
+
+    json!({
+        "before": before_label,
+        "after": after_label,
+        "per_endpoint": per_endpoint,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(scale: f64) -> Value {
+        json!({
+            "per_endpoint": {
+                "a": {
+                    "first_detection_rate": 0.5 * scale,
+                    "coverage": 0.9 * scale,
+                    "p50_latency_ms": 2.0 * scale,
+                    "p95_latency_ms": 8.0 * scale,
+                    "p99_latency_ms": 20.0 * scale,
+                    "observations": 1_000.0 * scale,
+                },
+            },
+        })
+    }
+
+    fn flags(before: &Value, after: &Value) -> Vec<(&'static str, bool, bool)> {
+        let diffs = diff_runs(before, after).unwrap();
+        assert_eq!(diffs.len(), 1);
+        diffs[0]
+            .metrics
+            .iter()
+            .map(|metric| (metric.key, metric.is_regression(), metric.is_improvement()))
+            .collect()
+    }
+
+    #[test]
+    fn higher_is_better_except_for_latency() {
+        let expected_when_increasing = [
+            ("first_detection_rate", false, true),
+            ("coverage", false, true),
+            ("p50_latency_ms", true, false),
+            ("p95_latency_ms", true, false),
+            ("p99_latency_ms", true, false),
+            ("observations", false, true),
+        ];
+        assert_eq!(flags(&run(1.0), &run(1.1)), expected_when_increasing);
+
+        let expected_when_decreasing: Vec<_> = expected_when_increasing
+            .iter()
+            .map(|(key, regression, improvement)| (*key, *improvement, *regression))
+            .collect();
+        assert_eq!(flags(&run(1.1), &run(1.0)), expected_when_decreasing);
+    }
+
+    #[test]
+    fn unchanged_or_missing_metrics_are_neither() {
+        let unchanged = flags(&run(1.0), &run(1.0 + 1e-10));
+        assert!(
+            unchanged
+                .iter()
+                .all(|(_, regression, improvement)| !regression && !improvement)
+        );
+
+        let gone = json!({ "per_endpoint": {} });
+        let missing = flags(&run(1.0), &gone);
+        assert!(
+            missing
+                .iter()
+                .all(|(_, regression, improvement)| !regression && !improvement)
+        );
+    }
+}
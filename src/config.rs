@@ -1,5 +1,5 @@
 use crate::proto::geyser::CommitmentLevel;
-use anyhow::{Context, Result, anyhow};
+use anyhow::{Context, Result, anyhow, bail};
 use serde::{
     Deserialize, Serialize,
     de::{self, Deserializer},
@@ -7,6 +7,8 @@ use serde::{
 use std::{fs, path::Path};
 
 const DEFAULT_ACCOUNT: &str = "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA";
+/// Size of the kernel CPU set that endpoint runtimes are pinned with.
+const MAX_CPU_CORES: usize = 1024;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ConfigToml {
@@ -14,10 +16,18 @@ pub struct ConfigToml {
     pub endpoint: Vec<Endpoint>,
     #[serde(default)]
     pub backend: BackendSettings,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prometheus: Option<PrometheusSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub continuous: Option<ContinuousSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leader_schedule: Option<LeaderScheduleSettings>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Config {
+    /// Signatures to compare before stopping: those every endpoint delivered,
+    /// plus those finalised by `late_threshold_ms` when it is set.
     pub transactions: i32,
     #[serde(
         alias = "account",
@@ -26,6 +36,44 @@ pub struct Config {
     )]
     pub accounts: Vec<String>,
     pub commitment: ArgsCommitment,
+    /// Stamp gRPC frames on socket receipt (kernel SO_TIMESTAMPING on Linux)
+    /// instead of after decoding.
+    #[serde(default)]
+    pub socket_timestamps: bool,
+    /// Run each endpoint on its own OS thread with a current-thread runtime.
+    #[serde(default)]
+    pub dedicated_runtimes: bool,
+    /// Only count signatures once every endpoint has a live subscription.
+    #[serde(default = "default_true")]
+    pub sync_start: bool,
+    /// Start counting anyway if some endpoint has not subscribed by then.
+    #[serde(default = "default_start_timeout_secs")]
+    pub start_timeout_secs: u64,
+    /// After `transactions` is reached, keep streams open up to this long so
+    /// slower endpoints can deliver signatures already seen elsewhere. Zero
+    /// stops immediately.
+    #[serde(default = "default_drain_secs")]
+    pub drain_secs: f64,
+    /// Stop the run after this many seconds, even if `transactions` is not reached.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<u64>,
+    /// Ignore signatures first seen within this many seconds of every stream
+    /// being connected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warmup_secs: Option<f64>,
+    /// Ignore the first this many signatures seen after every stream connected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warmup_signatures: Option<usize>,
+    /// Deliveries more than this many milliseconds after the first endpoint
+    /// count as late rather than as latency samples, and signatures stop
+    /// waiting for missing endpoints once it has passed. Those signatures then
+    /// count toward `transactions` like complete ones.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub late_threshold_ms: Option<f64>,
+    /// Delay percentiles to report, e.g. `[50, 90, 99, 99.9, 100]`; `100` is
+    /// the maximum.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percentiles: Option<Vec<f64>>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -35,12 +83,23 @@ pub struct Endpoint {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub x_token: Option<String>,
     pub kind: EndpointKind,
+    /// CPU core to pin this endpoint's dedicated runtime thread to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_core: Option<usize>,
 }
 
 fn default_true() -> bool {
     true
 }
 
+fn default_start_timeout_secs() -> u64 {
+    30
+}
+
+fn default_drain_secs() -> f64 {
+    5.0
+}
+
 #[derive(Debug, Default, Deserialize, Serialize, Clone)]
 pub struct BackendSettings {
     #[serde(default = "default_true")]
@@ -49,6 +108,60 @@ pub struct BackendSettings {
     pub url: Option<String>,
 }
 
+/// Serves live benchmark metrics for Prometheus to scrape.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PrometheusSettings {
+    /// Address to bind, e.g. `0.0.0.0:9898`. Metrics are served on `/metrics`.
+    pub listen: String,
+}
+
+/// Runs until stopped and reports a summary per rolling window.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ContinuousSettings {
+    #[serde(default = "default_window_secs")]
+    pub window_secs: u64,
+    /// How long to wait after a window closes for slower endpoints to deliver
+    /// its signatures before summarising and evicting them.
+    #[serde(default = "default_settle_secs")]
+    pub settle_secs: u64,
+    /// JSON Lines file that each window summary is appended to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub export: Option<String>,
+}
+
+/// Maps slots to the validator that produced them for a per-leader breakdown.
+/// Set exactly one of `file` and `rpc_url`.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LeaderScheduleSettings {
+    /// JSON file holding a `getLeaderSchedule` response for the run's epoch,
+    /// with the epoch's first slot added as `first_slot`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// RPC endpoint to fetch the schedule from after the run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc_url: Option<String>,
+}
+
+/// Rejects negative and non-finite numbers, which cannot become a `Duration`.
+fn ensure_non_negative(name: &str, value: f64) -> Result<()> {
+    if !(value.is_finite() && value >= 0.0) {
+        bail!(
+            "{} must be a finite, non-negative number, got {}",
+            name,
+            value
+        );
+    }
+    Ok(())
+}
+
+fn default_window_secs() -> u64 {
+    300
+}
+
+fn default_settle_secs() -> u64 {
+    10
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum EndpointKind {
@@ -102,20 +215,75 @@ impl EndpointKind {
     }
 }
 
+impl std::str::FromStr for EndpointKind {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "yellowstone" => Ok(EndpointKind::Yellowstone),
+            "arpc" => Ok(EndpointKind::Arpc),
+            "thor" => Ok(EndpointKind::Thor),
+            "shredstream" => Ok(EndpointKind::Shredstream),
+            "shreder" => Ok(EndpointKind::Shreder),
+            "jetstream" => Ok(EndpointKind::Jetstream),
+            other => Err(anyhow!("unknown endpoint kind {}", other)),
+        }
+    }
+}
+
 impl ConfigToml {
     pub fn load(path: &str) -> Result<Self> {
         let content =
             fs::read_to_string(path).with_context(|| format!("Failed to read config {}", path))?;
-        let config = toml::from_str(&content).map_err(|err| anyhow!(err))?;
+        let config: ConfigToml = toml::from_str(&content).map_err(|err| anyhow!(err))?;
+        config.validate()?;
         Ok(config)
     }
 
+    /// Rejects settings that would otherwise fail once the run has started.
+    fn validate(&self) -> Result<()> {
+        let cores = std::thread::available_parallelism()
+            .map(|cores| cores.get())
+            .unwrap_or(1)
+            .min(MAX_CPU_CORES);
+        for endpoint in &self.endpoint {
+            if let Some(core) = endpoint.cpu_core
+                && core >= cores
+            {
+                bail!(
+                    "endpoint {} has cpu_core = {}, but only cores 0-{} are available",
+                    endpoint.name,
+                    core,
+                    cores - 1
+                );
+            }
+        }
+        ensure_non_negative("drain_secs", self.config.drain_secs)?;
+        if let Some(warmup) = self.config.warmup_secs {
+            ensure_non_negative("warmup_secs", warmup)?;
+        }
+        if let Some(threshold) = self.config.late_threshold_ms {
+            ensure_non_negative("late_threshold_ms", threshold)?;
+        }
+        Ok(())
+    }
+
     pub fn create_default(path: &str) -> Result<Self> {
         let default_config = ConfigToml {
             config: Config {
                 transactions: 1000,
                 accounts: default_accounts(),
                 commitment: ArgsCommitment::Processed,
+                socket_timestamps: false,
+                dedicated_runtimes: false,
+                sync_start: true,
+                start_timeout_secs: default_start_timeout_secs(),
+                drain_secs: default_drain_secs(),
+                duration_secs: None,
+                warmup_secs: None,
+                warmup_signatures: None,
+                late_threshold_ms: None,
+                percentiles: None,
             },
             endpoint: vec![
                 Endpoint {
@@ -123,15 +291,20 @@ impl ConfigToml {
                     url: "http://fra.corvus-labs.io:10101".to_string(),
                     x_token: None,
                     kind: EndpointKind::Yellowstone,
+                    cpu_core: None,
                 },
                 Endpoint {
                     name: "arpc".to_string(),
                     url: "http://fra.corvus-labs.io:20202".to_string(),
                     x_token: None,
                     kind: EndpointKind::Arpc,
+                    cpu_core: None,
                 },
             ],
             backend: BackendSettings::default(),
+            prometheus: None,
+            continuous: None,
+            leader_schedule: None,
         };
 
         let toml_string = toml::to_string_pretty(&default_config)
@@ -0,0 +1,129 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use serde_json::json;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+use crate::{
+    analysis::{self, AnalysisOptions},
+    config::ContinuousSettings,
+    utils::{Comparator, StartBarrier},
+};
+
+/// Summarises and evicts signatures one window at a time while the benchmark
+/// keeps running.
+pub struct WindowReporter {
+    window: Duration,
+    settle: Duration,
+    comparator: Arc<Comparator>,
+    barrier: Arc<StartBarrier>,
+    endpoint_names: Vec<String>,
+    start_instant: Instant,
+    start_wallclock_secs: f64,
+    next_window: u32,
+    export: Option<File>,
+    options: AnalysisOptions,
+}
+
+impl WindowReporter {
+    pub fn new(
+        settings: &ContinuousSettings,
+        comparator: Arc<Comparator>,
+        barrier: Arc<StartBarrier>,
+        endpoint_names: Vec<String>,
+        start_instant: Instant,
+        start_wallclock_secs: f64,
+        options: AnalysisOptions,
+    ) -> Result<Self> {
+        let export = match settings.export.as_deref() {
+            Some(path) => Some(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .with_context(|| format!("Failed to open window export {}", path))?,
+            ),
+            None => None,
+        };
+
+        Ok(Self {
+            window: Duration::from_secs(settings.window_secs.max(1)),
+            settle: Duration::from_secs(settings.settle_secs),
+            comparator,
+            barrier,
+            endpoint_names,
+            start_instant,
+            start_wallclock_secs,
+            next_window: 0,
+            export,
+            options,
+        })
+    }
+
+    /// Reports each window once its settle time has passed, until shutdown.
+    pub async fn run(mut self, mut shutdown_rx: broadcast::Receiver<()>) -> Self {
+        loop {
+            let window_end = self.window * (self.next_window + 1);
+            let due = self.start_instant + window_end + self.settle;
+            tokio::select! {
+                _ = shutdown_rx.recv() => return self,
+                _ = tokio::time::sleep_until(due.into()) => {
+                    self.report(window_end, false);
+                }
+            }
+        }
+    }
+
+    /// Reports whatever remains as a final, possibly shorter, window.
+    pub fn finish(mut self) {
+        let elapsed = self.start_instant.elapsed();
+        self.report(elapsed.max(self.window * self.next_window), true);
+    }
+
+    fn report(&mut self, window_end: Duration, last: bool) {
+        let window_start = self.window * self.next_window;
+        let cutoff = if last { Duration::MAX } else { window_end };
+        let drained = self
+            .comparator
+            .drain_before(cutoff, self.window + self.settle);
+        let mut options = AnalysisOptions {
+            observed_until: Some(self.start_instant.elapsed()),
+            ..self.options.clone()
+        };
+        options.excluded_ranges.push(self.barrier.excluded_range());
+        let summary = analysis::compute_run_summary(&drained, &self.endpoint_names, &options);
+
+        println!(
+            "\nWindow {} ({:.0}s – {:.0}s)",
+            self.next_window + 1,
+            window_start.as_secs_f64(),
+            window_end.as_secs_f64()
+        );
+        analysis::display_run_summary(&summary);
+
+        if let Some(file) = self.export.as_mut() {
+            let line = json!({
+                "window": self.next_window + 1,
+                "start_wallclock_secs": self.start_wallclock_secs + window_start.as_secs_f64(),
+                "end_wallclock_secs": self.start_wallclock_secs + window_end.as_secs_f64(),
+                "metrics": analysis::build_metrics_report(&summary),
+            });
+            if let Err(err) = writeln!(file, "{}", line).and_then(|_| file.flush()) {
+                warn!(error = %err, "Failed to export window summary");
+            }
+        }
+
+        info!(
+            window = self.next_window + 1,
+            signatures = summary.total_signatures,
+            "Window summarised"
+        );
+        self.next_window += 1;
+    }
+}
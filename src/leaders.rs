@@ -0,0 +1,390 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::{Deserialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+
+use crate::{
+    config::LeaderScheduleSettings,
+    slots::consensus_slot,
+    stats::SignatureSample,
+    utils::{Comparator, percentile},
+};
+
+/// Leaders with fewer signatures are not judged.
+const MIN_LEADER_SIGNATURES: usize = 20;
+/// An endpoint is slow for a leader when its P50 delay on that leader's slots
+/// exceeds both this multiple of its overall P50 and the overall P50 plus
+/// `SLOW_LEADER_MIN_EXCESS_MS`.
+const SLOW_LEADER_FACTOR: f64 = 2.0;
+const SLOW_LEADER_MIN_EXCESS_MS: f64 = 1.0;
+/// Warmup epochs start at this length and double until the first normal one.
+const MINIMUM_SLOTS_PER_EPOCH: u64 = 32;
+/// Applies to each RPC request, including reading the response.
+const RPC_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where to read the leader schedule from.
+#[derive(Debug, Clone)]
+pub enum LeaderSource {
+    /// JSON file holding one epoch's `getLeaderSchedule` result and the
+    /// epoch's first slot.
+    File(PathBuf),
+    /// RPC endpoint queried for every epoch the run's slots span.
+    Rpc(String),
+}
+
+impl LeaderSource {
+    pub fn from_arg(value: &str) -> Self {
+        if value.starts_with("http://") || value.starts_with("https://") {
+            LeaderSource::Rpc(value.to_string())
+        } else {
+            LeaderSource::File(PathBuf::from(value))
+        }
+    }
+
+    pub fn from_settings(settings: &LeaderScheduleSettings) -> Result<Self> {
+        match (settings.file.as_deref(), settings.rpc_url.as_deref()) {
+            (Some(file), None) => Ok(LeaderSource::File(PathBuf::from(file))),
+            (None, Some(url)) => Ok(LeaderSource::Rpc(url.to_string())),
+            _ => bail!("leader_schedule needs exactly one of file or rpc_url"),
+        }
+    }
+}
+
+/// Slot to leader identity.
+#[derive(Debug, Default)]
+pub struct LeaderSchedule {
+    identities: Vec<String>,
+    leaders: HashMap<u64, usize>,
+}
+
+impl LeaderSchedule {
+    pub fn leader(&self, slot: u64) -> Option<&str> {
+        self.leaders
+            .get(&slot)
+            .map(|index| self.identities[*index].as_str())
+    }
+
+    pub fn slots(&self) -> usize {
+        self.leaders.len()
+    }
+
+    /// Adds a `getLeaderSchedule` result, whose slot indices are relative to
+    /// `first_slot`.
+    fn add_epoch(&mut self, first_slot: u64, schedule: HashMap<String, Vec<u64>>) {
+        for (identity, indices) in schedule {
+            let index = self.identities.len();
+            self.identities.push(identity);
+            for slot_index in indices {
+                self.leaders.insert(first_slot + slot_index, index);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EpochSchedule {
+    slots_per_epoch: u64,
+    warmup: bool,
+    first_normal_slot: u64,
+}
+
+impl EpochSchedule {
+    /// First slot and length of the epoch containing `slot`.
+    fn epoch_bounds(&self, slot: u64) -> (u64, u64) {
+        if self.warmup && slot < self.first_normal_slot {
+            let length = (slot + MINIMUM_SLOTS_PER_EPOCH + 1).next_power_of_two() / 2;
+            (length - MINIMUM_SLOTS_PER_EPOCH, length)
+        } else {
+            let index = (slot - self.first_normal_slot) % self.slots_per_epoch;
+            (slot - index, self.slots_per_epoch)
+        }
+    }
+}
+
+/// Loads the schedule for the slots recorded in `comparator`.
+pub async fn load_leader_schedule(
+    source: &LeaderSource,
+    comparator: &Comparator,
+) -> Result<LeaderSchedule> {
+    let (first, last) = slot_range(comparator)
+        .ok_or_else(|| anyhow!("No slots were recorded, so leaders cannot be looked up"))?;
+
+    let mut schedule = LeaderSchedule::default();
+    match source {
+        LeaderSource::File(path) => {
+            let file = read_schedule_file(path)?;
+            let length: u64 = file.result.values().map(|slots| slots.len() as u64).sum();
+            let end = file.first_slot + length;
+            if last < file.first_slot || first >= end {
+                bail!(
+                    "{} covers slots {}..{}, but the run saw slots {}..={}",
+                    path.display(),
+                    file.first_slot,
+                    end,
+                    first,
+                    last
+                );
+            }
+            schedule.add_epoch(file.first_slot, file.result);
+        }
+        LeaderSource::Rpc(url) => {
+            let client = reqwest::Client::builder()
+                .timeout(RPC_TIMEOUT)
+                .build()
+                .context("Failed to build the RPC client")?;
+            let epochs: EpochSchedule =
+                rpc_call(&client, url, "getEpochSchedule", json!([])).await?;
+            let mut slot = first;
+            while slot <= last {
+                let (first_slot, length) = epochs.epoch_bounds(slot);
+                let result: Option<HashMap<String, Vec<u64>>> =
+                    rpc_call(&client, url, "getLeaderSchedule", json!([first_slot])).await?;
+                let Some(result) = result else {
+                    bail!(
+                        "{} has no leader schedule for the epoch starting at slot {}",
+                        url,
+                        first_slot
+                    );
+                };
+                schedule.add_epoch(first_slot, result);
+                slot = first_slot + length;
+            }
+        }
+    }
+    Ok(schedule)
+}
+
+fn slot_range(comparator: &Comparator) -> Option<(u64, u64)> {
+    let slots = comparator.iter().flat_map(|entry| {
+        entry
+            .value()
+            .values()
+            .filter_map(|tx| tx.slot)
+            .collect::<Vec<_>>()
+    });
+    slots.fold(None, |range, slot| match range {
+        None => Some((slot, slot)),
+        Some((first, last)) => Some((first.min(slot), last.max(slot))),
+    })
+}
+
+/// A `getLeaderSchedule` response with the first slot of its epoch added, as
+/// the result's slot indices are relative to it.
+#[derive(Debug, Deserialize)]
+struct ScheduleFile {
+    first_slot: u64,
+    result: HashMap<String, Vec<u64>>,
+}
+
+fn read_schedule_file(path: &Path) -> Result<ScheduleFile> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read leader schedule {}", path.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("Invalid leader schedule in {}", path.display()))
+}
+
+async fn rpc_call<T: DeserializeOwned>(
+    client: &reqwest::Client,
+    url: &str,
+    method: &str,
+    params: Value,
+) -> Result<T> {
+    let response: Value = client
+        .post(url)
+        .json(&json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params}))
+        .send()
+        .await
+        .with_context(|| format!("{} request to {} failed", method, url))?
+        .error_for_status()?
+        .json()
+        .await?;
+    if let Some(error) = response.get("error") {
+        bail!("{} returned an error: {}", method, error);
+    }
+    let result = response.get("result").cloned().unwrap_or(Value::Null);
+    serde_json::from_value(result).with_context(|| format!("Unexpected {} response", method))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LeaderBreakdown {
+    pub endpoint_names: Vec<String>,
+    /// Leaders in descending order of signatures.
+    pub leaders: Vec<LeaderRow>,
+    pub slow: Vec<SlowLeader>,
+    /// Signatures whose slot is not in the schedule.
+    pub unmapped: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct LeaderRow {
+    pub identity: String,
+    pub slots: usize,
+    pub signatures: usize,
+    /// One per endpoint, in the run's endpoint order.
+    pub endpoints: Vec<LeaderEndpointStats>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LeaderEndpointStats {
+    pub firsts: usize,
+    pub delivered: usize,
+    pub p50_delay_ms: Option<f64>,
+}
+
+/// A leader whose slots one endpoint was consistently slow on.
+#[derive(Debug, Clone)]
+pub struct SlowLeader {
+    pub identity: String,
+    pub endpoint: String,
+    pub signatures: usize,
+    pub p50_delay_ms: f64,
+    pub baseline_p50_ms: f64,
+}
+
+/// Maps each compared signature's consensus slot to its leader and
+/// summarises every endpoint per leader.
+pub fn build_leader_breakdown(
+    samples: &[SignatureSample],
+    names: &[String],
+    schedule: &LeaderSchedule,
+) -> LeaderBreakdown {
+    let mut breakdown = LeaderBreakdown {
+        endpoint_names: names.to_vec(),
+        ..Default::default()
+    };
+
+    let mut by_leader: HashMap<&str, (Vec<u64>, Vec<&SignatureSample>)> = HashMap::new();
+    for sample in samples {
+        let Some(slot) = consensus_slot(&sample.slots) else {
+            continue;
+        };
+        let Some(leader) = schedule.leader(slot) else {
+            breakdown.unmapped += 1;
+            continue;
+        };
+        let (slots, leader_samples) = by_leader.entry(leader).or_default();
+        if !slots.contains(&slot) {
+            slots.push(slot);
+        }
+        leader_samples.push(sample);
+    }
+
+    let mut overall: Vec<Vec<f64>> = vec![Vec::new(); names.len()];
+    for sample in by_leader
+        .values()
+        .flat_map(|(_, leader_samples)| leader_samples)
+    {
+        for (endpoint, delay) in sample.delays_ms.iter().enumerate() {
+            if let Some(delay) = delay {
+                overall[endpoint].push(*delay);
+            }
+        }
+    }
+    let baselines: Vec<Option<f64>> = overall
+        .iter_mut()
+        .map(|delays| {
+            delays.sort_by(f64::total_cmp);
+            (!delays.is_empty()).then(|| percentile(delays, 0.5))
+        })
+        .collect();
+
+    for (identity, (slots, leader_samples)) in by_leader {
+        let mut endpoints = vec![LeaderEndpointStats::default(); names.len()];
+        let mut delays: Vec<Vec<f64>> = vec![Vec::new(); names.len()];
+        for sample in &leader_samples {
+            endpoints[sample.first].firsts += 1;
+            for (endpoint, delay) in sample.delays_ms.iter().enumerate() {
+                if let Some(delay) = delay {
+                    endpoints[endpoint].delivered += 1;
+                    delays[endpoint].push(*delay);
+                }
+            }
+        }
+
+        for (endpoint, (stats, sorted)) in endpoints.iter_mut().zip(&mut delays).enumerate() {
+            if sorted.is_empty() {
+                continue;
+            }
+            sorted.sort_by(f64::total_cmp);
+            let p50 = percentile(sorted, 0.5);
+            stats.p50_delay_ms = Some(p50);
+
+            let Some(baseline) = baselines[endpoint] else {
+                continue;
+            };
+            let threshold =
+                (baseline * SLOW_LEADER_FACTOR).max(baseline + SLOW_LEADER_MIN_EXCESS_MS);
+            if stats.delivered >= MIN_LEADER_SIGNATURES && p50 > threshold {
+                breakdown.slow.push(SlowLeader {
+                    identity: identity.to_string(),
+                    endpoint: names[endpoint].clone(),
+                    signatures: stats.delivered,
+                    p50_delay_ms: p50,
+                    baseline_p50_ms: baseline,
+                });
+            }
+        }
+
+        breakdown.leaders.push(LeaderRow {
+            identity: identity.to_string(),
+            slots: slots.len(),
+            signatures: leader_samples.len(),
+            endpoints,
+        });
+    }
+
+    breakdown.leaders.sort_by(|a, b| {
+        b.signatures
+            .cmp(&a.signatures)
+            .then(a.identity.cmp(&b.identity))
+    });
+    breakdown.slow.sort_by(|a, b| {
+        (b.p50_delay_ms / b.baseline_p50_ms.max(f64::EPSILON))
+            .total_cmp(&(a.p50_delay_ms / a.baseline_p50_ms.max(f64::EPSILON)))
+    });
+    breakdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TESTNET_LIKE: EpochSchedule = EpochSchedule {
+        slots_per_epoch: 8192,
+        warmup: true,
+        first_normal_slot: 8160,
+    };
+
+    #[test]
+    fn warmup_epochs_start_at_32_slots_and_double() {
+        assert_eq!(TESTNET_LIKE.epoch_bounds(0), (0, 32));
+        assert_eq!(TESTNET_LIKE.epoch_bounds(31), (0, 32));
+        assert_eq!(TESTNET_LIKE.epoch_bounds(32), (32, 64));
+        assert_eq!(TESTNET_LIKE.epoch_bounds(95), (32, 64));
+        assert_eq!(TESTNET_LIKE.epoch_bounds(96), (96, 128));
+        assert_eq!(TESTNET_LIKE.epoch_bounds(4064), (4064, 4096));
+        assert_eq!(TESTNET_LIKE.epoch_bounds(8159), (4064, 4096));
+    }
+
+    #[test]
+    fn normal_epochs_follow_the_first_normal_slot() {
+        assert_eq!(TESTNET_LIKE.epoch_bounds(8160), (8160, 8192));
+        assert_eq!(TESTNET_LIKE.epoch_bounds(16351), (8160, 8192));
+        assert_eq!(TESTNET_LIKE.epoch_bounds(16352), (16352, 8192));
+
+        let mainnet = EpochSchedule {
+            slots_per_epoch: 432_000,
+            warmup: false,
+            first_normal_slot: 0,
+        };
+        assert_eq!(mainnet.epoch_bounds(0), (0, 432_000));
+        assert_eq!(mainnet.epoch_bounds(300_123_456), (299_808_000, 432_000));
+    }
+}
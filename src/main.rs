@@ -17,67 +17,39 @@ pub use {
 
 mod analysis;
 mod backend;
+mod cli;
+mod compare;
 mod config;
+mod continuous;
+mod leaders;
+mod mock;
+mod observations;
+mod overhead;
+mod prometheus;
 mod proto;
 mod providers;
+mod report;
+mod runtime;
+mod slots;
+mod stats;
+mod timeseries;
 mod utils;
 
-use anyhow::{Result, anyhow};
+use anyhow::{Context, Result, anyhow, bail};
 use backend::{BackendStatus, StreamOptions};
+use cli::{CliArgs, Command};
 use crossbeam_queue::ArrayQueue;
+use providers::capture::{CaptureHeader, CaptureWriter, ReplayPacing, ReplaySource};
+use report::ReportFormat;
 use tracing::{debug, error, info, warn};
 use tracing_subscriber::EnvFilter;
-use utils::{Comparator, ProgressTracker, get_current_timestamp};
+use utils::{Comparator, DrainGate, ProgressTracker, StartBarrier, get_current_timestamp};
 const DEFAULT_CONFIG_PATH: &str = "config.toml";
 const DEFAULT_BACKEND_STREAM_URL: &str = "wss://gb.solstack.app/v1/benchmarks/stream";
 const MAX_STREAM_TRANSACTIONS: i32 = 100_000;
 const SIGNATURE_QUEUE_CAPACITY: usize = 1_024;
-
-struct CliArgs {
-    config_path: Option<String>,
-    disable_streaming: bool,
-}
-
-impl CliArgs {
-    fn parse() -> Self {
-        let mut args = env::args().skip(1);
-        let mut parsed = CliArgs {
-            config_path: None,
-            disable_streaming: false,
-        };
-
-        while let Some(arg) = args.next() {
-            match arg.as_str() {
-                "--config" => {
-                    let value = args.next().unwrap_or_else(|| {
-                        eprintln!("Missing value for --config");
-                        print_usage();
-                        std::process::exit(1);
-                    });
-                    parsed.config_path = Some(value);
-                }
-                "--private" => {
-                    parsed.disable_streaming = true;
-                }
-                "--help" | "-h" => {
-                    print_usage();
-                    std::process::exit(0);
-                }
-                other => {
-                    eprintln!("Unknown argument: {}", other);
-                    print_usage();
-                    std::process::exit(1);
-                }
-            }
-        }
-
-        parsed
-    }
-}
-
-fn print_usage() {
-    eprintln!("Usage: geyserbench [--config <PATH>] [--private]");
-}
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(50);
+const LATE_SWEEP_INTERVAL: Duration = Duration::from_millis(250);
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -90,13 +62,80 @@ async fn main() -> Result<()> {
         .map_err(|err| anyhow!(err))?;
 
     let cli = CliArgs::parse();
+    match cli.command {
+        Command::Benchmark | Command::Replay => run_benchmark(cli).await,
+        Command::Analyze => run_analyze(cli).await,
+        Command::Compare => run_compare(cli),
+        Command::MockServer => {
+            let config_path = cli
+                .config_path
+                .as_deref()
+                .unwrap_or(mock::DEFAULT_MOCK_CONFIG_PATH);
+            mock::run(config_path).await
+        }
+    }
+}
+
+async fn run_benchmark(mut cli: CliArgs) -> Result<()> {
     let config_path = cli.config_path.as_deref().unwrap_or(DEFAULT_CONFIG_PATH);
-    let config = config::ConfigToml::load_or_create(config_path)?;
+    let mut config = config::ConfigToml::load_or_create(config_path)?;
     info!(config_path = config_path, "Loaded configuration");
 
+    if let Some(path) = cli.observations_out.as_deref() {
+        observations::ObservationFormat::from_path(path)?;
+    }
+    let report_format = resolve_report(&mut cli)?;
+    let mut analysis_options = analysis::AnalysisOptions {
+        late_threshold_ms: config.config.late_threshold_ms,
+        ..Default::default()
+    };
+    if let Some(percents) = config.config.percentiles.as_ref() {
+        if percents.is_empty() || percents.iter().any(|p| !(*p > 0.0 && *p <= 100.0)) {
+            bail!("config.percentiles must be non-empty and within (0, 100]");
+        }
+        analysis_options.percentiles = percents.iter().map(|p| p / 100.0).collect();
+    }
+    if let Some(percentiles) = cli.percentiles.clone() {
+        analysis_options.percentiles = percentiles;
+    }
+    if let Some(bucket_secs) = cli.bucket_secs {
+        analysis_options.bucket_secs = bucket_secs;
+    }
+    analysis_options.group_by = cli.group_by.clone();
+    let leader_source = match (cli.leader_schedule.clone(), config.leader_schedule.as_ref()) {
+        (Some(source), _) => Some(source),
+        (None, Some(settings)) => Some(leaders::LeaderSource::from_settings(settings)?),
+        (None, None) => None,
+    };
+
+    let pacing = if cli.replay_fast {
+        ReplayPacing::Fast
+    } else {
+        ReplayPacing::Original
+    };
+    let replay_sources = match cli
+        .inputs
+        .first()
+        .filter(|_| cli.command == Command::Replay)
+    {
+        Some(dir) => Some(load_replay(&mut config, dir, pacing).await?),
+        None => None,
+    };
+    let record_dir = match (cli.record_dir, replay_sources.is_some()) {
+        (Some(_), true) => {
+            warn!("--record is ignored when replaying a capture");
+            None
+        }
+        (dir, _) => dir,
+    };
+
     let (shutdown_tx, _) = broadcast::channel::<()>(1);
 
-    let start_time_local = get_current_timestamp();
+    let start_time_local = replay_sources
+        .as_ref()
+        .and_then(|sources| sources.first())
+        .map(|source| source.origin_secs)
+        .unwrap_or_else(get_current_timestamp);
     let comparator = Arc::new(Comparator::new());
     let start_instant = Instant::now();
     let clock_offset_ms: f64;
@@ -114,8 +153,18 @@ async fn main() -> Result<()> {
         );
     }
 
+    let continuous_settings = config.continuous.clone();
+    if continuous_settings.is_some()
+        && (cli.observations_out.is_some() || cli.metrics_out.is_some() || report_format.is_some())
+    {
+        warn!("End-of-run outputs are not written in continuous mode; use continuous.export");
+    }
+
     let mut backend_settings = config.backend.clone();
-    backend_settings.enabled = !(cli.disable_streaming || high_transaction_volume);
+    backend_settings.enabled = !(cli.disable_streaming
+        || high_transaction_volume
+        || replay_sources.is_some()
+        || continuous_settings.is_some());
     backend_settings.url = Some(DEFAULT_BACKEND_STREAM_URL.to_string());
 
     let mut backend_handle = None;
@@ -205,20 +254,72 @@ async fn main() -> Result<()> {
 
     let mut handles = Vec::new();
     let endpoint_names: Vec<String> = config.endpoint.iter().map(|e| e.name.clone()).collect();
-    let global_target = if config.config.transactions > 0 {
+    let global_target = if continuous_settings.is_some() {
+        info!("Continuous mode enabled; running until stopped");
+        None
+    } else if config.config.transactions > 0 {
         Some(config.config.transactions as usize)
     } else {
         None
     };
     let progress_tracker = global_target.map(|target| Arc::new(ProgressTracker::new(target)));
+    let live_metrics = match config.prometheus.as_ref() {
+        Some(settings) => {
+            let metrics = Arc::new(prometheus::LiveMetrics::new(
+                &endpoint_names,
+                global_target,
+                config.config.late_threshold_ms,
+            ));
+            prometheus::serve(&settings.listen, metrics.clone()).await?;
+            Some(metrics)
+        }
+        None => None,
+    };
 
     let total_producers = config.endpoint.len();
+    let drain = (global_target.is_some() && config.config.drain_secs > 0.0).then(|| {
+        Arc::new(DrainGate::new(Duration::from_secs_f64(
+            config.config.drain_secs,
+        )))
+    });
+    let barrier = Arc::new(StartBarrier::new(
+        &endpoint_names,
+        start_instant,
+        config.config.sync_start,
+    ));
+    let mut runtime_monitors = Vec::with_capacity(total_producers);
+    let shared_lag = config
+        .endpoint
+        .iter()
+        .any(|endpoint| {
+            runtime::RuntimePlacement::for_endpoint(&config.config, endpoint)
+                == runtime::RuntimePlacement::Shared
+        })
+        .then(|| runtime::spawn_shared_lag_probe(shutdown_tx.subscribe()));
     for (index, endpoint) in config.endpoint.clone().into_iter().enumerate() {
         let provider = providers::create_provider(&endpoint.kind);
         let shared_config = config.config.clone();
         let signature_queue = signature_queues
             .as_ref()
             .and_then(|queues| queues.get(index).cloned());
+        let capture = match record_dir.as_deref() {
+            Some(dir) => {
+                let header = CaptureHeader {
+                    endpoint: endpoint.name.clone(),
+                    kind: endpoint.kind.as_str().to_string(),
+                    started_at_secs: start_time_local,
+                    accounts: shared_config.accounts.clone(),
+                };
+                Some(CaptureWriter::create(dir, &header).with_context(|| {
+                    format!(
+                        "Failed to create capture for {} in {}",
+                        endpoint.name,
+                        dir.display()
+                    )
+                })?)
+            }
+            None => None,
+        };
         let context = providers::ProviderContext {
             shutdown_tx: shutdown_tx.clone(),
             shutdown_rx: shutdown_tx.subscribe(),
@@ -231,9 +332,28 @@ async fn main() -> Result<()> {
             target_transactions: global_target,
             total_producers,
             progress: progress_tracker.clone(),
+            frame_tap: providers::transport::FrameTap::new(
+                shared_config.socket_timestamps,
+                capture.is_some(),
+            ),
+            overhead: Arc::new(overhead::ClientOverhead::new()),
+            capture,
+            replay: replay_sources
+                .as_ref()
+                .and_then(|sources| sources.get(index).cloned()),
+            metrics: live_metrics.clone(),
+            continuous: continuous_settings.is_some(),
+            barrier: barrier.clone(),
+            drain: drain.clone(),
         };
 
-        handles.push(provider.process(endpoint, shared_config, context));
+        let placement = runtime::RuntimePlacement::for_endpoint(&shared_config, &endpoint);
+        let name = endpoint.name.clone();
+        let overhead = context.overhead.clone();
+        let (handle, lag) =
+            runtime::launch_provider(provider, endpoint, shared_config, context, placement);
+        runtime_monitors.push((name, placement, lag, overhead));
+        handles.push(handle);
     }
 
     tokio::spawn({
@@ -257,6 +377,117 @@ async fn main() -> Result<()> {
         }
     });
 
+    if !barrier.is_open() {
+        let barrier = barrier.clone();
+        let timeout = Duration::from_secs(config.config.start_timeout_secs);
+        tokio::spawn(async move {
+            tokio::time::sleep_until((start_instant + timeout).into()).await;
+            if !barrier.is_open() {
+                warn!(
+                    pending = %barrier.pending().join(", "),
+                    timeout_secs = timeout.as_secs(),
+                    "Not every endpoint subscribed in time; counting signatures anyway"
+                );
+                barrier.open();
+            }
+        });
+    }
+
+    if let Some(drain) = drain.clone() {
+        let comparator = comparator.clone();
+        let shutdown_tx = shutdown_tx.clone();
+        tokio::spawn(async move {
+            let started = drain.started().await;
+            // Signatures first seen long before the target are unlikely to
+            // ever complete, so only recent ones are waited for.
+            let since = started
+                .saturating_duration_since(start_instant)
+                .saturating_sub(drain.timeout());
+            let deadline = started + drain.timeout();
+            loop {
+                let pending = comparator.pending_since(total_producers, since);
+                if pending == 0 {
+                    info!("Slower endpoints caught up; broadcasting shutdown");
+                    break;
+                }
+                if Instant::now() >= deadline {
+                    info!(pending, "Drain period elapsed; broadcasting shutdown");
+                    break;
+                }
+                tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+            }
+            let _ = shutdown_tx.send(());
+        });
+    }
+
+    if let Some(limit) = config.config.duration_secs {
+        let shutdown_tx = shutdown_tx.clone();
+        let shared_shutdown = shared_shutdown.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep_until((start_instant + Duration::from_secs(limit)).into()).await;
+            if !shared_shutdown.swap(true, Ordering::AcqRel) {
+                info!(
+                    duration_secs = limit,
+                    "Reached run duration; broadcasting shutdown"
+                );
+                let _ = shutdown_tx.send(());
+            }
+        });
+    }
+
+    if let Some(threshold_ms) = config.config.late_threshold_ms {
+        let completion = providers::common::SignatureCompletion {
+            shutdown_tx: shutdown_tx.clone(),
+            shared_counter: shared_counter.clone(),
+            shared_shutdown: shared_shutdown.clone(),
+            target_transactions: global_target,
+            progress: progress_tracker.clone(),
+            metrics: live_metrics.clone(),
+            drain: drain.clone(),
+            barrier: barrier.clone(),
+        };
+        let comparator = comparator.clone();
+        let mut shutdown_rx = shutdown_tx.subscribe();
+        let threshold = Duration::from_secs_f64(threshold_ms / 1_000.0);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(LATE_SWEEP_INTERVAL);
+            loop {
+                tokio::select! {
+                    _ = shutdown_rx.recv() => break,
+                    _ = ticker.tick() => {
+                        let deadline = start_instant.elapsed().saturating_sub(threshold);
+                        for (signature, observations) in
+                            comparator.expire_incomplete(total_producers, deadline)
+                        {
+                            debug!(signature = %signature, endpoints = observations.len(), "Signature passed late threshold");
+                            completion.complete("late_threshold", &observations);
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    let window_reporter = match continuous_settings.as_ref() {
+        Some(settings) => {
+            let reporter = continuous::WindowReporter::new(
+                settings,
+                comparator.clone(),
+                barrier.clone(),
+                endpoint_names.clone(),
+                start_instant,
+                start_time_local,
+                // Windows report the headline table only.
+                analysis::AnalysisOptions {
+                    group_by: Vec::new(),
+                    ..analysis_options.clone()
+                },
+            )?;
+            Some(tokio::spawn(reporter.run(shutdown_tx.subscribe())))
+        }
+        None => None,
+    };
+
     for handle in handles {
         match handle.await {
             Ok(Ok(_)) => {}
@@ -265,13 +496,68 @@ async fn main() -> Result<()> {
         }
     }
 
+    if let Some(reporter) = window_reporter {
+        // Streams may also end on their own, e.g. when a replay runs out.
+        let _ = shutdown_tx.send(());
+        match reporter.await {
+            Ok(reporter) => reporter.finish(),
+            Err(err) => error!(error = ?err, "Window reporter join error"),
+        }
+        return Ok(());
+    }
+
     let run_aborted = aborted.load(Ordering::Acquire);
 
+    let mut run_context = observations::RunContext {
+        counting_started_secs: barrier.excluded_range().1,
+        warmup_until_secs: None,
+        late_threshold_ms: config.config.late_threshold_ms,
+    };
     let run_summary = if !run_aborted {
-        Some(analysis::compute_run_summary(
+        let mut options = analysis_options;
+        options.excluded_ranges.push(barrier.excluded_range());
+        let connected_at = barrier.opened_at().unwrap_or_default();
+        if let Some(cutoff) = analysis::warmup_cutoff(
             comparator.as_ref(),
             &endpoint_names,
-        ))
+            connected_at,
+            config.config.warmup_secs,
+            config.config.warmup_signatures,
+        ) {
+            info!(
+                connected_at_secs = connected_at.as_secs_f64(),
+                warmup_until_secs = cutoff,
+                "Excluding warmup signatures from results"
+            );
+            options.excluded_ranges.push((0.0, cutoff));
+            run_context.warmup_until_secs = Some(cutoff);
+        }
+        if let Some(source) = leader_source.as_ref() {
+            match leaders::load_leader_schedule(source, comparator.as_ref()).await {
+                Ok(schedule) => {
+                    info!(slots = schedule.slots(), "Loaded leader schedule");
+                    options.leader_schedule = Some(Arc::new(schedule));
+                }
+                Err(err) => warn!(error = %err, "Skipping leader breakdown"),
+            }
+        }
+        let mut summary =
+            analysis::compute_run_summary(comparator.as_ref(), &endpoint_names, &options);
+        summary.counting_started_at = barrier.opened_at();
+        summary.stream_setups = barrier.setups();
+        summary.runtimes = runtime_monitors
+            .iter()
+            .map(
+                |(name, placement, lag, overhead)| analysis::EndpointRuntimeSummary {
+                    name: name.clone(),
+                    placement: placement.label(),
+                    event_loop_lag: lag.as_ref().and_then(|lag| lag.snapshot()),
+                    overhead: overhead.snapshot(),
+                },
+            )
+            .collect();
+        summary.shared_runtime_lag = shared_lag.as_ref().and_then(|lag| lag.snapshot());
+        Some(summary)
     } else {
         None
     };
@@ -317,8 +603,48 @@ async fn main() -> Result<()> {
     if !run_aborted {
         if let Some(summary) = run_summary.as_ref() {
             analysis::display_run_summary(summary);
+            analysis::display_lead_times(summary);
+            analysis::display_incidents(summary);
+            analysis::display_slots(summary);
+            analysis::display_leaders(summary);
+            analysis::display_groups(summary);
+            analysis::display_head_to_head(summary);
+            analysis::display_significance(summary);
+            analysis::display_client_overhead(summary);
+            analysis::display_stream_setup(summary);
             let metrics_json = analysis::build_metrics_report(summary);
             debug!(metrics = %metrics_json, "Computed run metrics");
+            if let Some(path) = cli.metrics_out.as_deref()
+                && let Err(err) = write_json(path, &metrics_json)
+            {
+                error!(path = %path.display(), error = %err, "Failed to write metrics");
+            }
+            if let Some(format) = report_format
+                && let Err(err) = report::write_report(summary, format, cli.report_out.as_deref())
+            {
+                error!(error = %err, "Failed to write report");
+            }
+            if let Some(path) = cli.timeseries_out.as_deref() {
+                match timeseries::write_timeline(&summary.timeline, path) {
+                    Ok(()) => info!(path = %path.display(), "Wrote time series"),
+                    Err(err) => error!(error = %err, "Failed to write time series"),
+                }
+            }
+            if let Some(path) = cli.slots_out.as_deref() {
+                match slots::write_slots(&summary.slots, path) {
+                    Ok(()) => info!(path = %path.display(), "Wrote slot breakdown"),
+                    Err(err) => error!(error = %err, "Failed to write slot breakdown"),
+                }
+            }
+        }
+
+        if let Some(path) = cli.observations_out.as_deref() {
+            match observations::write_observations(comparator.as_ref(), path, &run_context) {
+                Ok(rows) => info!(path = %path.display(), rows, "Wrote signature observations"),
+                Err(err) => {
+                    error!(path = %path.display(), error = %err, "Failed to write observations")
+                }
+            }
         }
 
         if let Some(run_id) = backend_run_id {
@@ -330,3 +656,187 @@ async fn main() -> Result<()> {
 
     Ok(())
 }
+
+/// Replaces the configured endpoints with the captures found in `dir` and
+/// returns one replay source per endpoint, in the same order.
+async fn load_replay(
+    config: &mut config::ConfigToml,
+    dir: &std::path::Path,
+    pacing: ReplayPacing,
+) -> Result<Vec<ReplaySource>> {
+    let captures = providers::capture::discover(dir)
+        .await
+        .with_context(|| format!("Failed to read captures from {}", dir.display()))?;
+    if captures.is_empty() {
+        bail!(
+            "No .{} files found in {}",
+            providers::capture::CAPTURE_EXTENSION,
+            dir.display()
+        );
+    }
+
+    let origin_secs = captures
+        .iter()
+        .map(|(_, header)| header.started_at_secs)
+        .fold(f64::INFINITY, f64::min);
+    let started = Instant::now();
+
+    let mut accounts: Vec<String> = Vec::new();
+    let mut endpoints = Vec::with_capacity(captures.len());
+    let mut sources = Vec::with_capacity(captures.len());
+    for (path, header) in captures {
+        for account in &header.accounts {
+            if !accounts.contains(account) {
+                accounts.push(account.clone());
+            }
+        }
+        endpoints.push(config::Endpoint {
+            name: header.endpoint,
+            url: path.display().to_string(),
+            x_token: None,
+            kind: header.kind.parse()?,
+            cpu_core: None,
+        });
+        sources.push(ReplaySource {
+            path,
+            pacing,
+            origin_secs,
+            started,
+        });
+    }
+
+    info!(
+        dir = %dir.display(),
+        endpoints = endpoints.len(),
+        ?pacing,
+        "Loaded captures for replay"
+    );
+    config.endpoint = endpoints;
+    if !accounts.is_empty() {
+        config.config.accounts = accounts;
+    }
+    Ok(sources)
+}
+
+/// Recomputes the run summary from a saved observations file.
+async fn run_analyze(mut cli: CliArgs) -> Result<()> {
+    let report_format = resolve_report(&mut cli)?;
+    let path = &cli.inputs[0];
+    let records = observations::read_observations(path)?;
+    info!(path = %path.display(), rows = records.len(), "Loaded observations");
+    let (comparator, available) = observations::load_comparator(records);
+    let context = observations::read_run_context(path)?;
+
+    let endpoint_names = match cli.endpoints {
+        Some(selected) => {
+            if let Some(unknown) = selected.iter().find(|name| !available.contains(name)) {
+                bail!(
+                    "Endpoint {} is not in {}; available endpoints: {}",
+                    unknown,
+                    path.display(),
+                    available.join(", ")
+                );
+            }
+            selected
+        }
+        None => available,
+    };
+
+    let mut options = analysis::AnalysisOptions {
+        excluded_ranges: cli.excluded_ranges,
+        late_threshold_ms: cli.late_threshold_ms,
+        group_by: cli.group_by,
+        ..Default::default()
+    };
+    match context {
+        Some(context) => context.apply(&mut options),
+        None => warn!(
+            path = %observations::context_path(path).display(),
+            "No run context found; signatures from before counting started and warmup are included"
+        ),
+    }
+    if let Some(percentiles) = cli.percentiles {
+        options.percentiles = percentiles;
+    }
+    if let Some(bucket_secs) = cli.bucket_secs {
+        options.bucket_secs = bucket_secs;
+    }
+    if let Some(source) = cli.leader_schedule.as_ref() {
+        let schedule = leaders::load_leader_schedule(source, &comparator).await?;
+        info!(slots = schedule.slots(), "Loaded leader schedule");
+        options.leader_schedule = Some(Arc::new(schedule));
+    }
+
+    let summary = analysis::compute_run_summary(&comparator, &endpoint_names, &options);
+    analysis::display_run_summary(&summary);
+    analysis::display_lead_times(&summary);
+    analysis::display_incidents(&summary);
+    analysis::display_slots(&summary);
+    analysis::display_leaders(&summary);
+    analysis::display_groups(&summary);
+    analysis::display_head_to_head(&summary);
+    analysis::display_significance(&summary);
+    if let Some(path) = cli.metrics_out.as_deref() {
+        write_json(path, &analysis::build_metrics_report(&summary))?;
+    }
+    if let Some(format) = report_format {
+        report::write_report(&summary, format, cli.report_out.as_deref())?;
+    }
+    if let Some(path) = cli.timeseries_out.as_deref() {
+        timeseries::write_timeline(&summary.timeline, path)?;
+        info!(path = %path.display(), "Wrote time series");
+    }
+    if let Some(path) = cli.slots_out.as_deref() {
+        slots::write_slots(&summary.slots, path)?;
+        info!(path = %path.display(), "Wrote slot breakdown");
+    }
+    Ok(())
+}
+
+/// Diffs two runs, each given as a metrics JSON file or an observations file.
+fn run_compare(cli: CliArgs) -> Result<()> {
+    let (before_path, after_path) = (&cli.inputs[0], &cli.inputs[1]);
+    let before = compare::load_run_metrics(before_path)?;
+    let after = compare::load_run_metrics(after_path)?;
+    let diffs = compare::diff_runs(&before, &after)?;
+
+    let before_label = before_path.display().to_string();
+    let after_label = after_path.display().to_string();
+    compare::display_run_diff(&diffs, &before_label, &after_label);
+    if let Some(path) = cli.metrics_out.as_deref() {
+        write_json(
+            path,
+            &compare::build_diff_report(&diffs, &before_label, &after_label),
+        )?;
+    }
+    Ok(())
+}
+
+/// `--report-format json` is an alias for `--metrics-out`: a JSON report
+/// written to a file becomes the metrics file, and only a JSON report printed
+/// to stdout is left to the report writer.
+fn resolve_report(cli: &mut CliArgs) -> Result<Option<ReportFormat>> {
+    let format = ReportFormat::resolve(cli.report_format, cli.report_out.as_deref())?;
+    if format != Some(ReportFormat::Json) {
+        return Ok(format);
+    }
+    let Some(path) = cli.report_out.take() else {
+        return Ok(format);
+    };
+    if cli
+        .metrics_out
+        .as_ref()
+        .is_some_and(|metrics| *metrics != path)
+    {
+        bail!("--report-format json is an alias for --metrics-out; pass only one of them");
+    }
+    cli.metrics_out = Some(path);
+    Ok(None)
+}
+
+fn write_json(path: &std::path::Path, value: &serde_json::Value) -> Result<()> {
+    let content = serde_json::to_string_pretty(value)?;
+    std::fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    info!(path = %path.display(), "Wrote metrics");
+    Ok(())
+}
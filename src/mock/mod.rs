@@ -0,0 +1,281 @@
+use std::{
+    fs,
+    net::SocketAddr,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+use futures::future::try_join_all;
+use serde::{Deserialize, Serialize};
+use tokio::{signal::ctrl_c, sync::broadcast};
+use tracing::{info, warn};
+
+use crate::{config::EndpointKind, providers::common::parse_tracked_accounts, utils::SplitMix64};
+
+mod services;
+
+pub const DEFAULT_MOCK_CONFIG_PATH: &str = "mock.toml";
+const DEFAULT_ACCOUNT: &str = "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA";
+const SCRIPT_BUFFER: usize = 16_384;
+const BASE_SLOT: u64 = 300_000_000;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct MockConfigToml {
+    pub script: ScriptSettings,
+    #[serde(rename = "server")]
+    pub servers: Vec<MockServerSettings>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ScriptSettings {
+    /// Accounts included in every synthetic transaction so provider filters match.
+    pub accounts: Vec<String>,
+    /// Synthetic signatures emitted per second.
+    pub rate: f64,
+    /// Total signatures to emit; `0` keeps emitting until interrupted.
+    #[serde(default)]
+    pub count: u64,
+    #[serde(default = "default_slot_ms")]
+    pub slot_ms: u64,
+    #[serde(default)]
+    pub seed: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MockServerSettings {
+    pub kind: EndpointKind,
+    pub listen: SocketAddr,
+    #[serde(default)]
+    pub delay_ms: f64,
+    #[serde(default)]
+    pub jitter_ms: f64,
+    /// Probability in `[0, 1]` that a signature is never delivered by this server.
+    #[serde(default)]
+    pub loss: f64,
+}
+
+fn default_slot_ms() -> u64 {
+    400
+}
+
+impl MockConfigToml {
+    pub fn load(path: &str) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read mock config {}", path))?;
+        let config: Self = toml::from_str(&content).map_err(|err| anyhow!(err))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn create_default(path: &str) -> Result<Self> {
+        let servers = [
+            (EndpointKind::Yellowstone, 10101, 0.0),
+            (EndpointKind::Arpc, 10102, 1.0),
+            (EndpointKind::Thor, 10103, 2.0),
+            (EndpointKind::Shredstream, 10104, 0.5),
+            (EndpointKind::Shreder, 10105, 1.5),
+            (EndpointKind::Jetstream, 10106, 3.0),
+        ]
+        .into_iter()
+        .map(|(kind, port, delay_ms)| MockServerSettings {
+            kind,
+            listen: SocketAddr::from(([127, 0, 0, 1], port)),
+            delay_ms,
+            jitter_ms: 0.5,
+            loss: 0.0,
+        })
+        .collect();
+
+        let default_config = MockConfigToml {
+            script: ScriptSettings {
+                accounts: vec![DEFAULT_ACCOUNT.to_string()],
+                rate: 50.0,
+                count: 0,
+                slot_ms: default_slot_ms(),
+                seed: 0,
+            },
+            servers,
+        };
+
+        let toml_string = toml::to_string_pretty(&default_config)
+            .context("Failed to serialize default mock config")?;
+        fs::write(path, toml_string)
+            .with_context(|| format!("Failed to write default mock config {}", path))?;
+
+        Ok(default_config)
+    }
+
+    pub fn load_or_create(path: &str) -> Result<Self> {
+        if Path::new(path).exists() {
+            Self::load(path)
+        } else {
+            Self::create_default(path)
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        if !(self.script.rate.is_finite() && self.script.rate > 0.0) {
+            bail!("script.rate must be a positive number");
+        }
+        if self.script.slot_ms == 0 {
+            bail!("script.slot_ms must be greater than zero");
+        }
+        if self.servers.is_empty() {
+            bail!("at least one [[server]] must be configured");
+        }
+        for server in &self.servers {
+            if !(0.0..=1.0).contains(&server.loss) {
+                bail!("server {} loss must be between 0 and 1", server.listen);
+            }
+            if server.delay_ms < 0.0 || server.jitter_ms < 0.0 {
+                bail!(
+                    "server {} delay and jitter must be non-negative",
+                    server.listen
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One synthetic transaction broadcast to every mock server.
+#[derive(Debug)]
+pub struct ScriptedTransaction {
+    pub sequence: u64,
+    pub slot: u64,
+    pub signature: [u8; 64],
+    pub fee_payer: [u8; 32],
+    pub tracked_accounts: Vec<[u8; 32]>,
+    pub emitted_at: Instant,
+}
+
+impl ScriptedTransaction {
+    pub fn account_keys(&self) -> Vec<Vec<u8>> {
+        std::iter::once(self.fee_payer.to_vec())
+            .chain(self.tracked_accounts.iter().map(|key| key.to_vec()))
+            .collect()
+    }
+}
+
+/// Per-server view of the script: applies that server's delay, jitter and loss.
+#[derive(Clone)]
+pub struct MockFeed {
+    script: broadcast::Sender<Arc<ScriptedTransaction>>,
+    settings: Arc<MockServerSettings>,
+    seed: u64,
+}
+
+impl MockFeed {
+    /// Delivery delay for a scripted signature, or `None` when it is dropped.
+    /// Deterministic for a given seed, server and sequence number.
+    fn delivery_delay(&self, sequence: u64) -> Option<Duration> {
+        let mut rng = SplitMix64::new(
+            self.seed ^ u64::from(self.settings.listen.port()).rotate_left(32) ^ sequence,
+        );
+        if rng.next_f64() < self.settings.loss {
+            return None;
+        }
+        let jitter = (rng.next_f64() * 2.0 - 1.0) * self.settings.jitter_ms;
+        let delay_ms = (self.settings.delay_ms + jitter).max(0.0);
+        Some(Duration::from_secs_f64(delay_ms / 1_000.0))
+    }
+}
+
+pub async fn run(config_path: &str) -> Result<()> {
+    let config = MockConfigToml::load_or_create(config_path)?;
+    config.validate()?;
+    info!(
+        config_path = config_path,
+        "Loaded mock server configuration"
+    );
+
+    let tracked_accounts = parse_tracked_accounts(&config.script.accounts)
+        .map_err(|err| anyhow!("invalid script account: {}", err))?
+        .into_iter()
+        .map(|pubkey| pubkey.to_bytes())
+        .collect::<Vec<_>>();
+
+    let (script_tx, _) = broadcast::channel(SCRIPT_BUFFER);
+    let mut servers = Vec::with_capacity(config.servers.len());
+    for settings in &config.servers {
+        let feed = MockFeed {
+            script: script_tx.clone(),
+            settings: Arc::new(settings.clone()),
+            seed: config.script.seed,
+        };
+        info!(
+            kind = settings.kind.as_str(),
+            url = %format!("http://{}", settings.listen),
+            delay_ms = settings.delay_ms,
+            jitter_ms = settings.jitter_ms,
+            loss = settings.loss,
+            "Serving mock feed"
+        );
+        servers.push(tokio::spawn(services::serve(feed)));
+    }
+
+    let script = tokio::spawn(run_script(
+        config.script.clone(),
+        tracked_accounts,
+        script_tx,
+    ));
+
+    tokio::select! {
+        result = try_join_all(servers) => {
+            for outcome in result? {
+                outcome?;
+            }
+        }
+        _ = ctrl_c() => info!("Received Ctrl+C; stopping mock servers"),
+    }
+
+    script.abort();
+    Ok(())
+}
+
+async fn run_script(
+    settings: ScriptSettings,
+    tracked_accounts: Vec<[u8; 32]>,
+    script_tx: broadcast::Sender<Arc<ScriptedTransaction>>,
+) {
+    let interval = Duration::from_secs_f64(1.0 / settings.rate);
+    let started = Instant::now();
+    let mut sequence = 0u64;
+
+    loop {
+        if settings.count > 0 && sequence >= settings.count {
+            info!(count = settings.count, "Mock script finished");
+            break;
+        }
+
+        let emit_at = started + interval.mul_f64(sequence as f64);
+        tokio::time::sleep_until(emit_at.into()).await;
+
+        let offset_ms = emit_at.duration_since(started).as_millis() as u64;
+        let transaction = ScriptedTransaction {
+            sequence,
+            slot: BASE_SLOT + offset_ms / settings.slot_ms,
+            signature: derive_bytes(settings.seed, b"signature", sequence),
+            fee_payer: derive_bytes(settings.seed, b"fee-payer", sequence),
+            tracked_accounts: tracked_accounts.clone(),
+            emitted_at: emit_at,
+        };
+
+        if script_tx.send(Arc::new(transaction)).is_err() && sequence == 0 {
+            warn!("No mock subscribers connected yet; early signatures are not delivered");
+        }
+        sequence += 1;
+    }
+}
+
+fn derive_bytes<const N: usize>(seed: u64, domain: &[u8], sequence: u64) -> [u8; N] {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&seed.to_le_bytes());
+    hasher.update(domain);
+    hasher.update(&sequence.to_le_bytes());
+    let mut output = [0u8; N];
+    hasher.finalize_xof().fill(&mut output);
+    output
+}
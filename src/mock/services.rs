@@ -0,0 +1,504 @@
+use std::{pin::Pin, sync::Arc};
+
+use anyhow::Result;
+use futures::Stream;
+use prost::Message;
+use solana_hash::Hash;
+use solana_message::{Message as LegacyMessage, MessageHeader, VersionedMessage};
+use solana_pubkey::Pubkey;
+use solana_signature::Signature;
+use solana_transaction::versioned::VersionedTransaction;
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status, Streaming, transport::Server};
+use tracing::{debug, warn};
+
+use crate::{
+    config::EndpointKind,
+    proto::{
+        geyser::{
+            self, GetBlockHeightRequest, GetBlockHeightResponse, GetLatestBlockhashRequest,
+            GetLatestBlockhashResponse, GetSlotRequest, GetSlotResponse, GetVersionRequest,
+            GetVersionResponse, IsBlockhashValidRequest, IsBlockhashValidResponse, PingRequest,
+            PongResponse, SubscribeReplayInfoRequest, SubscribeReplayInfoResponse,
+            geyser_server::{Geyser, GeyserServer},
+        },
+        solana::storage::confirmed_block,
+    },
+    providers::{
+        arpc::arpc::{
+            self,
+            arpc_service_server::{ArpcService, ArpcServiceServer},
+        },
+        jetstream::jetstream::{
+            self,
+            jetstream_server::{Jetstream, JetstreamServer},
+        },
+        shreder::shreder::{
+            self,
+            shreder_service_server::{ShrederService, ShrederServiceServer},
+        },
+        shredstream::shredstream::{
+            self,
+            shredstream_proxy_server::{ShredstreamProxy, ShredstreamProxyServer},
+        },
+        thor::{
+            publisher::{
+                self,
+                event_publisher_server::{EventPublisher, EventPublisherServer},
+            },
+            thor_streamer,
+        },
+    },
+};
+
+use super::{MockFeed, ScriptedTransaction};
+
+const SUBSCRIBER_BUFFER: usize = 4_096;
+
+type FeedStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send + 'static>>;
+
+pub async fn serve(feed: MockFeed) -> Result<()> {
+    let addr = feed.settings.listen;
+    let router = match feed.settings.kind {
+        EndpointKind::Yellowstone => {
+            Server::builder().add_service(GeyserServer::new(MockGeyser(feed)))
+        }
+        EndpointKind::Arpc => Server::builder().add_service(ArpcServiceServer::new(MockArpc(feed))),
+        EndpointKind::Thor => {
+            Server::builder().add_service(EventPublisherServer::new(MockThor(feed)))
+        }
+        EndpointKind::Shredstream => {
+            Server::builder().add_service(ShredstreamProxyServer::new(MockShredstream(feed)))
+        }
+        EndpointKind::Shreder => {
+            Server::builder().add_service(ShrederServiceServer::new(MockShreder(feed)))
+        }
+        EndpointKind::Jetstream => {
+            Server::builder().add_service(JetstreamServer::new(MockJetstream(feed)))
+        }
+    };
+
+    router.serve(addr).await?;
+    Ok(())
+}
+
+impl MockFeed {
+    /// Streams the script to one subscriber, encoding each delivered signature
+    /// with `encode` after the server's configured delay.
+    fn subscribe<T, F>(&self, encode: F) -> FeedStream<T>
+    where
+        T: Send + 'static,
+        F: Fn(&ScriptedTransaction) -> T + Send + Sync + 'static,
+    {
+        let mut script_rx = self.script.subscribe();
+        let (message_tx, message_rx) = mpsc::channel(SUBSCRIBER_BUFFER);
+        let feed = self.clone();
+        let encode = Arc::new(encode);
+
+        tokio::spawn(async move {
+            loop {
+                let transaction = match script_rx.recv().await {
+                    Ok(transaction) => transaction,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(listen = %feed.settings.listen, skipped, "Mock subscriber lagged behind script");
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                if message_tx.is_closed() {
+                    debug!(listen = %feed.settings.listen, "Mock subscriber disconnected");
+                    break;
+                }
+
+                let Some(delay) = feed.delivery_delay(transaction.sequence) else {
+                    continue;
+                };
+                let deliver_at = transaction.emitted_at + delay;
+                let message = encode(&transaction);
+                let message_tx = message_tx.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep_until(deliver_at.into()).await;
+                    let _ = message_tx.send(Ok(message)).await;
+                });
+            }
+        });
+
+        Box::pin(ReceiverStream::new(message_rx))
+    }
+}
+
+fn message_header(transaction: &ScriptedTransaction) -> (u32, u32, u32) {
+    (1, 0, transaction.tracked_accounts.len() as u32)
+}
+
+struct MockGeyser(MockFeed);
+
+#[tonic::async_trait]
+impl Geyser for MockGeyser {
+    type SubscribeStream = FeedStream<geyser::SubscribeUpdate>;
+
+    async fn subscribe(
+        &self,
+        _request: Request<Streaming<geyser::SubscribeRequest>>,
+    ) -> Result<Response<Self::SubscribeStream>, Status> {
+        Ok(Response::new(self.0.subscribe(|transaction| {
+            let (required, readonly_signed, readonly_unsigned) = message_header(transaction);
+            geyser::SubscribeUpdate {
+                filters: vec!["account".to_string()],
+                update_oneof: Some(geyser::subscribe_update::UpdateOneof::Transaction(
+                    geyser::SubscribeUpdateTransaction {
+                        transaction: Some(geyser::SubscribeUpdateTransactionInfo {
+                            signature: transaction.signature.to_vec(),
+                            transaction: Some(confirmed_block::Transaction {
+                                signatures: vec![transaction.signature.to_vec()],
+                                message: Some(confirmed_block::Message {
+                                    header: Some(confirmed_block::MessageHeader {
+                                        num_required_signatures: required,
+                                        num_readonly_signed_accounts: readonly_signed,
+                                        num_readonly_unsigned_accounts: readonly_unsigned,
+                                    }),
+                                    account_keys: transaction.account_keys(),
+                                    ..Default::default()
+                                }),
+                            }),
+                            meta: Some(confirmed_block::TransactionStatusMeta::default()),
+                            index: transaction.sequence,
+                            ..Default::default()
+                        }),
+                        slot: transaction.slot,
+                    },
+                )),
+                ..Default::default()
+            }
+        })))
+    }
+
+    async fn subscribe_replay_info(
+        &self,
+        _request: Request<SubscribeReplayInfoRequest>,
+    ) -> Result<Response<SubscribeReplayInfoResponse>, Status> {
+        Ok(Response::new(SubscribeReplayInfoResponse::default()))
+    }
+
+    async fn ping(&self, request: Request<PingRequest>) -> Result<Response<PongResponse>, Status> {
+        Ok(Response::new(PongResponse {
+            count: request.into_inner().count,
+        }))
+    }
+
+    async fn get_latest_blockhash(
+        &self,
+        _request: Request<GetLatestBlockhashRequest>,
+    ) -> Result<Response<GetLatestBlockhashResponse>, Status> {
+        Err(Status::unimplemented(
+            "mock server only streams transactions",
+        ))
+    }
+
+    async fn get_block_height(
+        &self,
+        _request: Request<GetBlockHeightRequest>,
+    ) -> Result<Response<GetBlockHeightResponse>, Status> {
+        Err(Status::unimplemented(
+            "mock server only streams transactions",
+        ))
+    }
+
+    async fn get_slot(
+        &self,
+        _request: Request<GetSlotRequest>,
+    ) -> Result<Response<GetSlotResponse>, Status> {
+        Err(Status::unimplemented(
+            "mock server only streams transactions",
+        ))
+    }
+
+    async fn is_blockhash_valid(
+        &self,
+        _request: Request<IsBlockhashValidRequest>,
+    ) -> Result<Response<IsBlockhashValidResponse>, Status> {
+        Err(Status::unimplemented(
+            "mock server only streams transactions",
+        ))
+    }
+
+    async fn get_version(
+        &self,
+        _request: Request<GetVersionRequest>,
+    ) -> Result<Response<GetVersionResponse>, Status> {
+        Ok(Response::new(GetVersionResponse {
+            version: format!("geyserbench-mock {}", env!("CARGO_PKG_VERSION")),
+        }))
+    }
+}
+
+struct MockArpc(MockFeed);
+
+#[tonic::async_trait]
+impl ArpcService for MockArpc {
+    type SubscribeStream = FeedStream<arpc::SubscribeResponse>;
+
+    async fn subscribe(
+        &self,
+        _request: Request<Streaming<arpc::SubscribeRequest>>,
+    ) -> Result<Response<Self::SubscribeStream>, Status> {
+        Ok(Response::new(self.0.subscribe(|transaction| {
+            let (required, readonly_signed, readonly_unsigned) = message_header(transaction);
+            arpc::SubscribeResponse {
+                filters: vec!["account".to_string()],
+                transaction: Some(arpc::SubscribeResponseTransaction {
+                    slot: transaction.slot,
+                    num_required_signatures: required,
+                    num_readonly_signed_accounts: readonly_signed,
+                    num_readonly_unsigned_accounts: readonly_unsigned,
+                    signatures: vec![transaction.signature.to_vec()],
+                    account_keys: transaction.account_keys(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }
+        })))
+    }
+}
+
+struct MockThor(MockFeed);
+
+#[tonic::async_trait]
+impl EventPublisher for MockThor {
+    type SubscribeToTransactionsStream = FeedStream<publisher::StreamResponse>;
+    type SubscribeToSlotStatusStream = FeedStream<publisher::StreamResponse>;
+    type SubscribeToWalletTransactionsStream = FeedStream<publisher::StreamResponse>;
+    type SubscribeToAccountUpdatesStream = FeedStream<publisher::StreamResponse>;
+
+    async fn subscribe_to_transactions(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<Self::SubscribeToTransactionsStream>, Status> {
+        Ok(Response::new(self.0.subscribe(|transaction| {
+            let (required, readonly_signed, readonly_unsigned) = message_header(transaction);
+            let wrapper = thor_streamer::MessageWrapper {
+                event_message: Some(thor_streamer::message_wrapper::EventMessage::Transaction(
+                    thor_streamer::TransactionEventWrapper {
+                        stream_type: thor_streamer::StreamType::Filtered as i32,
+                        transaction: Some(thor_streamer::TransactionEvent {
+                            slot: transaction.slot,
+                            signature: transaction.signature.to_vec(),
+                            index: transaction.sequence,
+                            transaction: Some(thor_streamer::SanitizedTransaction {
+                                message: Some(thor_streamer::Message {
+                                    header: Some(thor_streamer::MessageHeader {
+                                        num_required_signatures: required,
+                                        num_readonly_signed_accounts: readonly_signed,
+                                        num_readonly_unsigned_accounts: readonly_unsigned,
+                                    }),
+                                    account_keys: transaction.account_keys(),
+                                    ..Default::default()
+                                }),
+                                signatures: vec![transaction.signature.to_vec()],
+                                ..Default::default()
+                            }),
+                            transaction_status_meta: Some(
+                                thor_streamer::TransactionStatusMeta::default(),
+                            ),
+                            ..Default::default()
+                        }),
+                    },
+                )),
+            };
+            publisher::StreamResponse {
+                data: wrapper.encode_to_vec(),
+            }
+        })))
+    }
+
+    async fn subscribe_to_slot_status(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<Self::SubscribeToSlotStatusStream>, Status> {
+        Err(Status::unimplemented(
+            "mock server only streams transactions",
+        ))
+    }
+
+    async fn subscribe_to_wallet_transactions(
+        &self,
+        _request: Request<publisher::SubscribeWalletRequest>,
+    ) -> Result<Response<Self::SubscribeToWalletTransactionsStream>, Status> {
+        Err(Status::unimplemented(
+            "mock server only streams transactions",
+        ))
+    }
+
+    async fn subscribe_to_account_updates(
+        &self,
+        _request: Request<publisher::SubscribeAccountsRequest>,
+    ) -> Result<Response<Self::SubscribeToAccountUpdatesStream>, Status> {
+        Err(Status::unimplemented(
+            "mock server only streams transactions",
+        ))
+    }
+}
+
+struct MockShredstream(MockFeed);
+
+#[tonic::async_trait]
+impl ShredstreamProxy for MockShredstream {
+    type SubscribeEntriesStream = FeedStream<shredstream::Entry>;
+
+    async fn subscribe_entries(
+        &self,
+        _request: Request<shredstream::SubscribeEntriesRequest>,
+    ) -> Result<Response<Self::SubscribeEntriesStream>, Status> {
+        Ok(Response::new(self.0.subscribe(|transaction| {
+            shredstream::Entry {
+                slot: transaction.slot,
+                entries: encode_entries(transaction),
+            }
+        })))
+    }
+}
+
+/// Serialises a scripted transaction the way Jito's proxy ships entries: a
+/// bincode `Vec<solana_entry::entry::Entry>`.
+fn encode_entries(transaction: &ScriptedTransaction) -> Vec<u8> {
+    let (required, readonly_signed, readonly_unsigned) = message_header(transaction);
+    let account_keys = std::iter::once(Pubkey::new_from_array(transaction.fee_payer))
+        .chain(
+            transaction
+                .tracked_accounts
+                .iter()
+                .map(|key| Pubkey::new_from_array(*key)),
+        )
+        .collect();
+    let message = LegacyMessage {
+        header: MessageHeader {
+            num_required_signatures: required as u8,
+            num_readonly_signed_accounts: readonly_signed as u8,
+            num_readonly_unsigned_accounts: readonly_unsigned as u8,
+        },
+        account_keys,
+        recent_blockhash: Hash::default(),
+        instructions: Vec::new(),
+    };
+    let entry = solana_entry::entry::Entry {
+        num_hashes: 1,
+        hash: Hash::default(),
+        transactions: vec![VersionedTransaction {
+            signatures: vec![Signature::from(transaction.signature)],
+            message: VersionedMessage::Legacy(message),
+        }],
+    };
+    bincode::serialize(&vec![entry]).unwrap_or_default()
+}
+
+struct MockShreder(MockFeed);
+
+#[tonic::async_trait]
+impl ShrederService for MockShreder {
+    type SubscribeEntriesStream = FeedStream<shreder::Entry>;
+    type SubscribeTransactionsStream = FeedStream<shreder::SubscribeTransactionsResponse>;
+
+    async fn subscribe_entries(
+        &self,
+        _request: Request<shreder::SubscribeEntriesRequest>,
+    ) -> Result<Response<Self::SubscribeEntriesStream>, Status> {
+        Ok(Response::new(self.0.subscribe(|transaction| {
+            shreder::Entry {
+                slot: transaction.slot,
+                entries: encode_entries(transaction),
+            }
+        })))
+    }
+
+    async fn subscribe_transactions(
+        &self,
+        _request: Request<Streaming<shreder::SubscribeTransactionsRequest>>,
+    ) -> Result<Response<Self::SubscribeTransactionsStream>, Status> {
+        Ok(Response::new(self.0.subscribe(|transaction| {
+            let (required, readonly_signed, readonly_unsigned) = message_header(transaction);
+            shreder::SubscribeTransactionsResponse {
+                filters: vec!["account".to_string()],
+                transaction: Some(shreder::SubscribeUpdateTransaction {
+                    transaction: Some(shreder::Transaction {
+                        signatures: vec![transaction.signature.to_vec()],
+                        message: Some(shreder::Message {
+                            header: Some(shreder::MessageHeader {
+                                num_required_signatures: required,
+                                num_readonly_signed_accounts: readonly_signed,
+                                num_readonly_unsigned_accounts: readonly_unsigned,
+                            }),
+                            account_keys: transaction.account_keys(),
+                            ..Default::default()
+                        }),
+                    }),
+                    slot: transaction.slot,
+                }),
+                ..Default::default()
+            }
+        })))
+    }
+}
+
+struct MockJetstream(MockFeed);
+
+#[tonic::async_trait]
+impl Jetstream for MockJetstream {
+    type SubscribeStream = FeedStream<jetstream::SubscribeUpdate>;
+    type SubscribeParsedStream = FeedStream<jetstream::SubscribeUpdateParsedTransaction>;
+
+    async fn subscribe(
+        &self,
+        _request: Request<Streaming<jetstream::SubscribeRequest>>,
+    ) -> Result<Response<Self::SubscribeStream>, Status> {
+        Ok(Response::new(self.0.subscribe(|transaction| {
+            let (required, readonly_signed, readonly_unsigned) = message_header(transaction);
+            jetstream::SubscribeUpdate {
+                filters: vec!["account".to_string()],
+                update_oneof: Some(jetstream::subscribe_update::UpdateOneof::Transaction(
+                    jetstream::SubscribeUpdateTransaction {
+                        transaction: Some(jetstream::SubscribeUpdateTransactionInfo {
+                            signature: transaction.signature.to_vec(),
+                            slot: transaction.slot,
+                            num_required_signatures: required,
+                            num_readonly_signed_accounts: readonly_signed,
+                            num_readonly_unsigned_accounts: readonly_unsigned,
+                            signatures: vec![transaction.signature.to_vec()],
+                            account_keys: transaction.account_keys(),
+                            ..Default::default()
+                        }),
+                        slot: transaction.slot,
+                    },
+                )),
+                ..Default::default()
+            }
+        })))
+    }
+
+    async fn subscribe_parsed(
+        &self,
+        _request: Request<Streaming<jetstream::SubscribeParsedRequest>>,
+    ) -> Result<Response<Self::SubscribeParsedStream>, Status> {
+        Err(Status::unimplemented(
+            "mock server only streams transactions",
+        ))
+    }
+
+    async fn ping(
+        &self,
+        request: Request<jetstream::PingRequest>,
+    ) -> Result<Response<jetstream::PongResponse>, Status> {
+        Ok(Response::new(jetstream::PongResponse {
+            count: request.into_inner().count,
+        }))
+    }
+
+    async fn get_version(
+        &self,
+        _request: Request<jetstream::GetVersionRequest>,
+    ) -> Result<Response<jetstream::GetVersionResponse>, Status> {
+        Ok(Response::new(jetstream::GetVersionResponse {
+            version: format!("geyserbench-mock {}", env!("CARGO_PKG_VERSION")),
+        }))
+    }
+}
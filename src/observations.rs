@@ -0,0 +1,600 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+use arrow_array::{
+    Array, ArrayRef, BooleanArray, Float64Array, RecordBatch, StringArray, UInt64Array,
+};
+use arrow_schema::{DataType, Field, Schema};
+use parquet::{
+    arrow::{ArrowWriter, arrow_reader::ParquetRecordBatchReaderBuilder},
+    basic::Compression,
+    file::properties::WriterProperties,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    analysis::AnalysisOptions,
+    utils::{Comparator, TransactionData, TransactionTags},
+};
+
+/// Separates keys in the `accounts` and `programs` columns.
+const KEY_SEPARATOR: char = ';';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObservationFormat {
+    Jsonl,
+    Csv,
+    Parquet,
+}
+
+impl ObservationFormat {
+    pub fn from_path(path: &Path) -> Result<Self> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("jsonl" | "ndjson") => Ok(ObservationFormat::Jsonl),
+            Some("csv") => Ok(ObservationFormat::Csv),
+            Some("parquet") => Ok(ObservationFormat::Parquet),
+            _ => bail!(
+                "Cannot infer observation format from {}; use a .jsonl, .csv or .parquet extension",
+                path.display()
+            ),
+        }
+    }
+}
+
+/// One endpoint's sighting of one signature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObservationRecord {
+    pub signature: String,
+    pub endpoint: String,
+    pub wallclock_secs: f64,
+    /// Time since the run started, in milliseconds.
+    pub elapsed_ms: f64,
+    pub start_wallclock_secs: f64,
+    pub receive_wallclock_secs: Option<f64>,
+    pub slot: Option<u64>,
+    pub backfilled: bool,
+    /// Tracked accounts the transaction referenced, `;`-separated.
+    #[serde(default)]
+    pub accounts: String,
+    /// Programs the transaction invoked, `;`-separated.
+    #[serde(default)]
+    pub programs: String,
+    #[serde(default)]
+    pub succeeded: Option<bool>,
+    /// Compute-unit price in micro-lamports.
+    #[serde(default)]
+    pub cu_price: Option<u64>,
+    #[serde(default)]
+    pub jito_tip: bool,
+}
+
+/// What decided which signatures the run counted, saved beside its
+/// observations so `analyze` and `compare` reproduce the run's summary.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunContext {
+    /// Seconds since run start at which counting started. Signatures first
+    /// seen earlier were left out.
+    pub counting_started_secs: f64,
+    /// End of the warmup period, in seconds since run start.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warmup_until_secs: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub late_threshold_ms: Option<f64>,
+}
+
+impl RunContext {
+    /// Leaves out what the run left out. A late threshold already set, e.g.
+    /// from the command line, takes precedence over the run's.
+    pub fn apply(&self, options: &mut AnalysisOptions) {
+        options
+            .excluded_ranges
+            .push((0.0, self.counting_started_secs));
+        if let Some(warmup_until) = self.warmup_until_secs {
+            options.excluded_ranges.push((0.0, warmup_until));
+        }
+        if options.late_threshold_ms.is_none() {
+            options.late_threshold_ms = self.late_threshold_ms;
+        }
+    }
+}
+
+/// Sidecar holding the `RunContext` of an observations file, e.g.
+/// `observations.run.json` for `observations.jsonl`.
+pub fn context_path(path: &Path) -> PathBuf {
+    path.with_extension("run.json")
+}
+
+/// Reads the run context saved beside `path`. Returns `None` for files
+/// written without one.
+pub fn read_run_context(path: &Path) -> Result<Option<RunContext>> {
+    let context_path = context_path(path);
+    if !context_path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&context_path)
+        .with_context(|| format!("Failed to read run context {}", context_path.display()))?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .with_context(|| format!("Failed to parse run context {}", context_path.display()))
+}
+
+/// Flattens the comparator into one record per signature and endpoint,
+/// ordered by wall-clock time.
+pub fn collect_observations(comparator: &Comparator) -> Vec<ObservationRecord> {
+    let mut records = Vec::new();
+    for entry in comparator.iter() {
+        for (endpoint, tx) in entry.value() {
+            records.push(ObservationRecord {
+                signature: entry.key().clone(),
+                endpoint: endpoint.clone(),
+                wallclock_secs: tx.wallclock_secs,
+                elapsed_ms: tx.elapsed_since_start.as_secs_f64() * 1_000.0,
+                start_wallclock_secs: tx.start_wallclock_secs,
+                receive_wallclock_secs: tx.receive_wallclock_secs,
+                slot: tx.slot,
+                backfilled: tx.wallclock_secs < tx.start_wallclock_secs,
+                accounts: join_keys(&tx.tags.accounts),
+                programs: join_keys(&tx.tags.programs),
+                succeeded: tx.tags.succeeded,
+                cu_price: tx.tags.cu_price,
+                jito_tip: tx.tags.jito_tip,
+            });
+        }
+    }
+
+    records.sort_by(|lhs, rhs| {
+        lhs.wallclock_secs
+            .total_cmp(&rhs.wallclock_secs)
+            .then_with(|| lhs.signature.cmp(&rhs.signature))
+            .then_with(|| lhs.endpoint.cmp(&rhs.endpoint))
+    });
+    records
+}
+
+/// Writes the observations and their run context. Returns the number of
+/// rows written.
+pub fn write_observations(
+    comparator: &Comparator,
+    path: &Path,
+    context: &RunContext,
+) -> Result<usize> {
+    let format = ObservationFormat::from_path(path)?;
+    // JSON has no infinity; a run that never started counting excluded
+    // everything anyway.
+    let context = RunContext {
+        counting_started_secs: context.counting_started_secs.min(f64::MAX),
+        warmup_until_secs: context.warmup_until_secs.map(|secs| secs.min(f64::MAX)),
+        ..context.clone()
+    };
+    let context_path = context_path(path);
+    fs::write(&context_path, serde_json::to_vec_pretty(&context)?)
+        .with_context(|| format!("Failed to write run context {}", context_path.display()))?;
+    let records = collect_observations(comparator);
+    let file = File::create(path)
+        .with_context(|| format!("Failed to create observations file {}", path.display()))?;
+
+    match format {
+        ObservationFormat::Jsonl => write_jsonl(file, &records)?,
+        ObservationFormat::Csv => write_csv(file, &records)?,
+        ObservationFormat::Parquet => write_parquet(file, &records)?,
+    }
+
+    Ok(records.len())
+}
+
+fn write_jsonl(file: File, records: &[ObservationRecord]) -> Result<()> {
+    let mut writer = BufWriter::new(file);
+    for record in records {
+        serde_json::to_writer(&mut writer, record)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_csv(file: File, records: &[ObservationRecord]) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(BufWriter::new(file));
+    for record in records {
+        writer.serialize(record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn observation_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("signature", DataType::Utf8, false),
+        Field::new("endpoint", DataType::Utf8, false),
+        Field::new("wallclock_secs", DataType::Float64, false),
+        Field::new("elapsed_ms", DataType::Float64, false),
+        Field::new("start_wallclock_secs", DataType::Float64, false),
+        Field::new("receive_wallclock_secs", DataType::Float64, true),
+        Field::new("slot", DataType::UInt64, true),
+        Field::new("backfilled", DataType::Boolean, false),
+        Field::new("accounts", DataType::Utf8, false),
+        Field::new("programs", DataType::Utf8, false),
+        Field::new("succeeded", DataType::Boolean, true),
+        Field::new("cu_price", DataType::UInt64, true),
+        Field::new("jito_tip", DataType::Boolean, false),
+    ])
+}
+
+fn write_parquet(file: File, records: &[ObservationRecord]) -> Result<()> {
+    let schema = Arc::new(observation_schema());
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(
+            records.iter().map(|record| record.signature.as_str()),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            records.iter().map(|record| record.endpoint.as_str()),
+        )),
+        Arc::new(Float64Array::from_iter_values(
+            records.iter().map(|record| record.wallclock_secs),
+        )),
+        Arc::new(Float64Array::from_iter_values(
+            records.iter().map(|record| record.elapsed_ms),
+        )),
+        Arc::new(Float64Array::from_iter_values(
+            records.iter().map(|record| record.start_wallclock_secs),
+        )),
+        Arc::new(Float64Array::from_iter(
+            records.iter().map(|record| record.receive_wallclock_secs),
+        )),
+        Arc::new(UInt64Array::from_iter(
+            records.iter().map(|record| record.slot),
+        )),
+        Arc::new(BooleanArray::from_iter(
+            records.iter().map(|record| Some(record.backfilled)),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            records.iter().map(|record| record.accounts.as_str()),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            records.iter().map(|record| record.programs.as_str()),
+        )),
+        Arc::new(BooleanArray::from_iter(
+            records.iter().map(|record| record.succeeded),
+        )),
+        Arc::new(UInt64Array::from_iter(
+            records.iter().map(|record| record.cu_price),
+        )),
+        Arc::new(BooleanArray::from_iter(
+            records.iter().map(|record| Some(record.jito_tip)),
+        )),
+    ];
+    let batch = RecordBatch::try_new(schema.clone(), columns)?;
+
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(file, schema, Some(properties))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+pub fn read_observations(path: &Path) -> Result<Vec<ObservationRecord>> {
+    let format = ObservationFormat::from_path(path)?;
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open observations file {}", path.display()))?;
+
+    match format {
+        ObservationFormat::Jsonl => read_jsonl(file),
+        ObservationFormat::Csv => read_csv(file),
+        ObservationFormat::Parquet => read_parquet(file),
+    }
+    .with_context(|| format!("Failed to parse observations file {}", path.display()))
+}
+
+/// Rebuilds a comparator from saved observations. Returns it with the
+/// endpoint names found in the records, sorted.
+pub fn load_comparator(records: Vec<ObservationRecord>) -> (Comparator, Vec<String>) {
+    let mut per_endpoint: HashMap<String, HashMap<String, TransactionData>> = HashMap::new();
+    for record in records {
+        let data = TransactionData {
+            wallclock_secs: record.wallclock_secs,
+            elapsed_since_start: Duration::from_secs_f64((record.elapsed_ms / 1_000.0).max(0.0)),
+            start_wallclock_secs: record.start_wallclock_secs,
+            receive_wallclock_secs: record.receive_wallclock_secs,
+            slot: record.slot,
+            tags: TransactionTags {
+                accounts: split_keys(&record.accounts),
+                programs: split_keys(&record.programs),
+                succeeded: record.succeeded,
+                cu_price: record.cu_price,
+                jito_tip: record.jito_tip,
+            },
+        };
+        per_endpoint
+            .entry(record.endpoint)
+            .or_default()
+            .insert(record.signature, data);
+    }
+
+    let comparator = Comparator::new();
+    let mut endpoint_names: Vec<String> = per_endpoint.keys().cloned().collect();
+    endpoint_names.sort();
+    for (endpoint, transactions) in per_endpoint {
+        comparator.add_batch(&endpoint, transactions);
+    }
+    (comparator, endpoint_names)
+}
+
+fn read_jsonl(file: File) -> Result<Vec<ObservationRecord>> {
+    let mut records = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = serde_json::from_str(&line)
+            .with_context(|| format!("invalid record on line {}", index + 1))?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+fn read_csv(file: File) -> Result<Vec<ObservationRecord>> {
+    let mut reader = csv::Reader::from_reader(BufReader::new(file));
+    reader
+        .deserialize()
+        .map(|record| record.map_err(|err| anyhow!(err)))
+        .collect()
+}
+
+fn read_parquet(file: File) -> Result<Vec<ObservationRecord>> {
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)?.build()?;
+    let mut records = Vec::new();
+    for batch in reader {
+        let batch = batch?;
+        let signature = column::<StringArray>(&batch, "signature")?;
+        let endpoint = column::<StringArray>(&batch, "endpoint")?;
+        let wallclock = column::<Float64Array>(&batch, "wallclock_secs")?;
+        let elapsed = column::<Float64Array>(&batch, "elapsed_ms")?;
+        let start = column::<Float64Array>(&batch, "start_wallclock_secs")?;
+        let received = column::<Float64Array>(&batch, "receive_wallclock_secs")?;
+        let slot = column::<UInt64Array>(&batch, "slot")?;
+        let backfilled = column::<BooleanArray>(&batch, "backfilled")?;
+        // Files written before tagging have no account or program columns.
+        let accounts = optional_column::<StringArray>(&batch, "accounts")?;
+        let programs = optional_column::<StringArray>(&batch, "programs")?;
+        let succeeded = optional_column::<BooleanArray>(&batch, "succeeded")?;
+        let cu_price = optional_column::<UInt64Array>(&batch, "cu_price")?;
+        let jito_tip = optional_column::<BooleanArray>(&batch, "jito_tip")?;
+
+        for row in 0..batch.num_rows() {
+            records.push(ObservationRecord {
+                signature: signature.value(row).to_string(),
+                endpoint: endpoint.value(row).to_string(),
+                wallclock_secs: wallclock.value(row),
+                elapsed_ms: elapsed.value(row),
+                start_wallclock_secs: start.value(row),
+                receive_wallclock_secs: received.is_valid(row).then(|| received.value(row)),
+                slot: slot.is_valid(row).then(|| slot.value(row)),
+                backfilled: backfilled.value(row),
+                accounts: accounts
+                    .map(|column| column.value(row).to_string())
+                    .unwrap_or_default(),
+                programs: programs
+                    .map(|column| column.value(row).to_string())
+                    .unwrap_or_default(),
+                succeeded: succeeded
+                    .filter(|column| column.is_valid(row))
+                    .map(|column| column.value(row)),
+                cu_price: cu_price
+                    .filter(|column| column.is_valid(row))
+                    .map(|column| column.value(row)),
+                jito_tip: jito_tip.is_some_and(|column| column.value(row)),
+            });
+        }
+    }
+    Ok(records)
+}
+
+fn optional_column<'a, T: 'static>(batch: &'a RecordBatch, name: &str) -> Result<Option<&'a T>> {
+    match batch.column_by_name(name) {
+        Some(_) => column(batch, name).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn join_keys(keys: &[String]) -> String {
+    keys.join(&KEY_SEPARATOR.to_string())
+}
+
+fn split_keys(joined: &str) -> Vec<String> {
+    joined
+        .split(KEY_SEPARATOR)
+        .filter(|key| !key.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn column<'a, T: 'static>(batch: &'a RecordBatch, name: &str) -> Result<&'a T> {
+    batch
+        .column_by_name(name)
+        .and_then(|column| column.as_any().downcast_ref::<T>())
+        .ok_or_else(|| anyhow!("missing or mistyped column {}", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis;
+
+    const START_SECS: f64 = 1_700_000_000.0;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("geyserbench-observations-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    fn observation(at_ms: u64, slot: u64) -> TransactionData {
+        let elapsed = Duration::from_millis(at_ms);
+        TransactionData {
+            wallclock_secs: START_SECS + elapsed.as_secs_f64(),
+            elapsed_since_start: elapsed,
+            start_wallclock_secs: START_SECS,
+            receive_wallclock_secs: None,
+            slot: Some(slot),
+            tags: TransactionTags::default(),
+        }
+    }
+
+    /// One signature every 100 ms for 5 s; `b` is 3 ms behind `a` and misses
+    /// every tenth one.
+    fn run_comparator() -> Comparator {
+        let comparator = Comparator::new();
+        for index in 0..50u64 {
+            let at_ms = index * 100;
+            let signature = format!("sig{index}");
+            comparator.add_batch(
+                "a",
+                HashMap::from([(signature.clone(), observation(at_ms, index))]),
+            );
+            if index % 10 != 9 {
+                comparator.add_batch(
+                    "b",
+                    HashMap::from([(signature, observation(at_ms + 3 + index % 4, index))]),
+                );
+            }
+        }
+        comparator
+    }
+
+    #[test]
+    fn analysing_saved_observations_reproduces_the_run() {
+        let comparator = run_comparator();
+        let names = vec!["a".to_string(), "b".to_string()];
+        let context = RunContext {
+            counting_started_secs: 0.5,
+            warmup_until_secs: Some(1.25),
+            late_threshold_ms: Some(50.0),
+        };
+        let mut live = AnalysisOptions {
+            late_threshold_ms: context.late_threshold_ms,
+            ..Default::default()
+        };
+        live.excluded_ranges
+            .push((0.0, context.counting_started_secs));
+        live.excluded_ranges.push((0.0, 1.25));
+        let expected = analysis::build_metrics_report(&analysis::compute_run_summary(
+            &comparator,
+            &names,
+            &live,
+        ));
+
+        let path = temp_path("reproduce.jsonl");
+        write_observations(&comparator, &path, &context).unwrap();
+        let saved = read_run_context(&path).unwrap().expect("run context");
+        assert_eq!(saved, context);
+        let (loaded, loaded_names) = load_comparator(read_observations(&path).unwrap());
+        assert_eq!(loaded_names, names);
+        let mut options = AnalysisOptions::default();
+        saved.apply(&mut options);
+        let reproduced = analysis::build_metrics_report(&analysis::compute_run_summary(
+            &loaded, &names, &options,
+        ));
+
+        assert_eq!(reproduced, expected);
+        assert!(expected["total_signatures"].as_u64().unwrap() < 50);
+        let without_context = analysis::build_metrics_report(&analysis::compute_run_summary(
+            &loaded,
+            &names,
+            &AnalysisOptions::default(),
+        ));
+        assert_ne!(
+            without_context["total_signatures"],
+            expected["total_signatures"]
+        );
+    }
+
+    #[test]
+    fn runs_that_never_started_counting_exclude_everything() {
+        let path = temp_path("never-started.csv");
+        let context = RunContext {
+            counting_started_secs: f64::INFINITY,
+            ..Default::default()
+        };
+        write_observations(&run_comparator(), &path, &context).unwrap();
+        let saved = read_run_context(&path).unwrap().unwrap();
+        let mut options = AnalysisOptions::default();
+        saved.apply(&mut options);
+        let (loaded, names) = load_comparator(read_observations(&path).unwrap());
+        let summary = analysis::compute_run_summary(&loaded, &names, &options);
+        assert_eq!(summary.total_signatures, 0);
+    }
+
+    fn tagged(at_ms: u64, slot: Option<u64>, tags: TransactionTags) -> TransactionData {
+        TransactionData {
+            receive_wallclock_secs: Some(START_SECS + at_ms as f64 / 1_000.0 - 0.000_25),
+            slot,
+            tags,
+            ..observation(at_ms, 0)
+        }
+    }
+
+    #[test]
+    fn observations_round_trip_through_every_format() {
+        let comparator = Comparator::new();
+        let swap = TransactionTags {
+            accounts: vec!["acct1".to_string(), "acct2".to_string()],
+            programs: vec!["prog1".to_string(), "prog2".to_string()],
+            succeeded: Some(false),
+            cu_price: Some(25_000),
+            jito_tip: true,
+        };
+        comparator.add_batch(
+            "a",
+            HashMap::from([
+                ("swap".to_string(), tagged(1_234, Some(42), swap.clone())),
+                ("plain".to_string(), observation(1_500, 43)),
+            ]),
+        );
+        comparator.add_batch(
+            "b",
+            HashMap::from([(
+                "swap".to_string(),
+                tagged(1_240, None, TransactionTags::default()),
+            )]),
+        );
+
+        for name in ["round-trip.jsonl", "round-trip.csv", "round-trip.parquet"] {
+            let path = temp_path(name);
+            let written = write_observations(&comparator, &path, &RunContext::default()).unwrap();
+            assert_eq!(written, 3, "{name}");
+            let (loaded, names) = load_comparator(read_observations(&path).unwrap());
+            assert_eq!(names, ["a", "b"], "{name}");
+
+            for entry in comparator.iter() {
+                let reloaded = loaded
+                    .iter()
+                    .find(|other| other.key() == entry.key())
+                    .unwrap();
+                for (endpoint, expected) in entry.value() {
+                    let actual = &reloaded.value()[endpoint];
+                    let drift = actual
+                        .arrival_elapsed()
+                        .abs_diff(expected.arrival_elapsed());
+                    assert!(drift < Duration::from_micros(1), "{name}: {drift:?}");
+                    assert_eq!(actual.wallclock_secs, expected.wallclock_secs, "{name}");
+                    assert_eq!(
+                        actual.receive_wallclock_secs, expected.receive_wallclock_secs,
+                        "{name}"
+                    );
+                    assert_eq!(actual.slot, expected.slot, "{name}");
+                    assert_eq!(actual.tags, expected.tags, "{name}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use hdrhistogram::Histogram;
+
+use crate::utils::get_current_timestamp;
+
+const STAGE_HISTOGRAM_MAX_NS: u64 = 60_000_000_000;
+
+fn new_stage_histogram() -> Histogram<u64> {
+    Histogram::new_with_bounds(1, STAGE_HISTOGRAM_MAX_NS, 3).expect("valid stage histogram bounds")
+}
+
+/// Time geyserbench itself spends on each stage of a provider's hot path.
+#[derive(Debug, Clone)]
+pub struct StageHistograms {
+    decode: Histogram<u64>,
+    filter: Histogram<u64>,
+    record: Histogram<u64>,
+    /// Frames that had no transport receive timestamp and fell back to the
+    /// time the provider saw them.
+    untimed_frames: u64,
+}
+
+impl Default for StageHistograms {
+    fn default() -> Self {
+        Self {
+            decode: new_stage_histogram(),
+            filter: new_stage_histogram(),
+            record: new_stage_histogram(),
+            untimed_frames: 0,
+        }
+    }
+}
+
+impl StageHistograms {
+    fn record_stage(histogram: &mut Histogram<u64>, duration: Duration) {
+        let nanos = (duration.as_nanos() as u64).clamp(1, STAGE_HISTOGRAM_MAX_NS);
+        histogram.saturating_record(nanos);
+    }
+
+    pub fn record_decode(&mut self, duration: Duration) {
+        Self::record_stage(&mut self.decode, duration);
+    }
+
+    pub fn record_filter(&mut self, duration: Duration) {
+        Self::record_stage(&mut self.filter, duration);
+    }
+
+    pub fn record_record(&mut self, duration: Duration) {
+        Self::record_stage(&mut self.record, duration);
+    }
+
+    pub fn count_untimed_frame(&mut self) {
+        self.untimed_frames += 1;
+    }
+}
+
+/// Per-endpoint overhead collected by the provider task and published when the
+/// stream closes.
+#[derive(Debug, Default)]
+pub struct ClientOverhead {
+    stages: Mutex<StageHistograms>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StageSnapshot {
+    pub samples: u64,
+    pub p50_us: f64,
+    pub p99_us: f64,
+    pub max_us: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OverheadSnapshot {
+    pub decode: Option<StageSnapshot>,
+    pub filter: Option<StageSnapshot>,
+    pub record: Option<StageSnapshot>,
+    pub untimed_frames: u64,
+}
+
+impl ClientOverhead {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn merge(&self, local: &StageHistograms) {
+        if let Ok(mut stages) = self.stages.lock() {
+            let _ = stages.decode.add(&local.decode);
+            let _ = stages.filter.add(&local.filter);
+            let _ = stages.record.add(&local.record);
+            stages.untimed_frames += local.untimed_frames;
+        }
+    }
+
+    pub fn snapshot(&self) -> OverheadSnapshot {
+        let Ok(stages) = self.stages.lock() else {
+            return OverheadSnapshot::default();
+        };
+
+        OverheadSnapshot {
+            decode: stage_snapshot(&stages.decode),
+            filter: stage_snapshot(&stages.filter),
+            record: stage_snapshot(&stages.record),
+            untimed_frames: stages.untimed_frames,
+        }
+    }
+}
+
+fn stage_snapshot(histogram: &Histogram<u64>) -> Option<StageSnapshot> {
+    if histogram.is_empty() {
+        return None;
+    }
+
+    Some(StageSnapshot {
+        samples: histogram.len(),
+        p50_us: histogram.value_at_quantile(0.5) as f64 / 1_000.0,
+        p99_us: histogram.value_at_quantile(0.99) as f64 / 1_000.0,
+        max_us: histogram.max() as f64 / 1_000.0,
+    })
+}
+
+/// Timing state for the stream message currently being processed.
+///
+/// The decode stage runs from the transport receive timestamp (when socket
+/// timestamps are enabled) to the message reaching the provider, so it
+/// includes time queued in the HTTP/2 and gRPC layers as well as tonic's own
+/// decoding, plus any explicit decoding the provider performs itself.
+#[derive(Debug)]
+pub struct FrameTimer {
+    pub received_at: Option<f64>,
+    mark: Instant,
+    decode: Duration,
+    filter: Option<Duration>,
+}
+
+impl FrameTimer {
+    pub fn start(received_at: Option<f64>) -> Self {
+        let transport_lag = received_at
+            .map(|received| (get_current_timestamp() - received).max(0.0))
+            .map(Duration::from_secs_f64)
+            .unwrap_or_default();
+
+        Self {
+            received_at,
+            mark: Instant::now(),
+            decode: transport_lag,
+            filter: None,
+        }
+    }
+
+    pub fn mark_decoded(&mut self) {
+        let now = Instant::now();
+        self.decode += now.saturating_duration_since(self.mark);
+        self.mark = now;
+    }
+
+    pub fn mark_filtered(&mut self) {
+        let now = Instant::now();
+        *self.filter.get_or_insert_default() += now.saturating_duration_since(self.mark);
+        self.mark = now;
+    }
+
+    /// Restarts the stage clock so time spent in `record` is not attributed to
+    /// the next filter pass over the same frame.
+    pub fn resume(&mut self) {
+        self.mark = Instant::now();
+    }
+
+    pub fn flush(self, stages: &mut StageHistograms) {
+        if !self.decode.is_zero() {
+            stages.record_decode(self.decode);
+        }
+        if let Some(filter) = self.filter {
+            stages.record_filter(filter);
+        }
+    }
+}
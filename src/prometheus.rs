@@ -0,0 +1,426 @@
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    net::SocketAddr,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use tracing::{debug, info, warn};
+
+use crate::utils::{TransactionData, get_current_timestamp};
+
+/// Upper bounds of the delay-to-first histogram buckets, in milliseconds.
+const DELAY_BUCKETS_MS: [f64; 14] = [
+    0.5, 1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0, 1_000.0, 2_000.0, 5_000.0, 10_000.0,
+];
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+/// Scrapers that have not sent their request headers by then are dropped.
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+type CounterFn = fn(&EndpointMetrics) -> u64;
+
+#[derive(Debug, Default)]
+struct DelayHistogram {
+    buckets: [AtomicU64; DELAY_BUCKETS_MS.len()],
+    count: AtomicU64,
+    sum_us: AtomicU64,
+}
+
+impl DelayHistogram {
+    fn observe(&self, delay_ms: f64) {
+        if let Some(index) = DELAY_BUCKETS_MS.iter().position(|bound| delay_ms <= *bound) {
+            self.buckets[index].fetch_add(1, Ordering::Relaxed);
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_us
+            .fetch_add((delay_ms * 1_000.0) as u64, Ordering::Relaxed);
+    }
+}
+
+/// Live counters for one endpoint. Updated from the provider hot path.
+#[derive(Debug, Default)]
+pub struct EndpointMetrics {
+    signatures: AtomicU64,
+    observations: AtomicU64,
+    first_detections: AtomicU64,
+    backfill: AtomicU64,
+    messages: AtomicU64,
+    stream_errors: AtomicU64,
+    late: AtomicU64,
+    connected: AtomicBool,
+    last_message_ms: AtomicU64,
+    delay: DelayHistogram,
+}
+
+impl EndpointMetrics {
+    pub fn stream_opened(&self) {
+        self.connected.store(true, Ordering::Relaxed);
+    }
+
+    pub fn stream_closed(&self) {
+        self.connected.store(false, Ordering::Relaxed);
+    }
+
+    pub fn stream_error(&self) {
+        self.stream_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn message_received(&self) {
+        self.messages.fetch_add(1, Ordering::Relaxed);
+        self.last_message_ms.store(
+            (get_current_timestamp() * 1_000.0) as u64,
+            Ordering::Relaxed,
+        );
+    }
+
+    pub fn signature_recorded(&self) {
+        self.signatures.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Metrics exported on `/metrics` while a benchmark runs.
+#[derive(Debug)]
+pub struct LiveMetrics {
+    endpoints: Vec<(String, Arc<EndpointMetrics>)>,
+    target: Option<usize>,
+    late_threshold_ms: Option<f64>,
+    compared: AtomicU64,
+    backfilled: AtomicU64,
+}
+
+impl LiveMetrics {
+    pub fn new(
+        endpoint_names: &[String],
+        target: Option<usize>,
+        late_threshold_ms: Option<f64>,
+    ) -> Self {
+        Self {
+            endpoints: endpoint_names
+                .iter()
+                .map(|name| (name.clone(), Arc::new(EndpointMetrics::default())))
+                .collect(),
+            target,
+            late_threshold_ms,
+            compared: AtomicU64::new(0),
+            backfilled: AtomicU64::new(0),
+        }
+    }
+
+    pub fn endpoint(&self, name: &str) -> Option<Arc<EndpointMetrics>> {
+        self.endpoints
+            .iter()
+            .find(|(candidate, _)| candidate == name)
+            .map(|(_, metrics)| metrics.clone())
+    }
+
+    fn get(&self, name: &str) -> Option<&EndpointMetrics> {
+        self.endpoints
+            .iter()
+            .find(|(candidate, _)| candidate == name)
+            .map(|(_, metrics)| metrics.as_ref())
+    }
+
+    /// Scores a signature once every endpoint has seen it, using the same
+    /// rules as the end-of-run summary.
+    pub fn record_signature(&self, observations: &HashMap<String, TransactionData>) {
+        let is_historical = observations
+            .values()
+            .any(|tx| tx.wallclock_secs < tx.start_wallclock_secs);
+        if is_historical {
+            self.backfilled.fetch_add(1, Ordering::Relaxed);
+            for endpoint in observations.keys() {
+                if let Some(metrics) = self.get(endpoint) {
+                    metrics.backfill.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            return;
+        }
+
+        let Some((first_endpoint, first_tx)) = observations
+            .iter()
+            .min_by_key(|(_, tx)| tx.arrival_elapsed())
+        else {
+            return;
+        };
+        self.compared.fetch_add(1, Ordering::Relaxed);
+
+        for (endpoint, tx) in observations {
+            let Some(metrics) = self.get(endpoint) else {
+                continue;
+            };
+            let delay_ms = tx
+                .arrival_elapsed()
+                .saturating_sub(first_tx.arrival_elapsed())
+                .as_secs_f64()
+                * 1_000.0;
+            if self
+                .late_threshold_ms
+                .is_some_and(|threshold| delay_ms > threshold)
+            {
+                metrics.late.fetch_add(1, Ordering::Relaxed);
+                continue;
+            }
+            metrics.observations.fetch_add(1, Ordering::Relaxed);
+            if endpoint == first_endpoint {
+                metrics.first_detections.fetch_add(1, Ordering::Relaxed);
+            }
+            metrics.delay.observe(delay_ms);
+        }
+    }
+
+    /// Renders the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let now_ms = (get_current_timestamp() * 1_000.0) as u64;
+
+        write_header(
+            &mut out,
+            "geyserbench_signatures_compared_total",
+            "counter",
+            "Signatures scored: seen by every endpoint, or finalised by the late threshold.",
+        );
+        let _ = writeln!(
+            out,
+            "geyserbench_signatures_compared_total {}",
+            self.compared.load(Ordering::Relaxed)
+        );
+        write_header(
+            &mut out,
+            "geyserbench_signatures_backfilled_total",
+            "counter",
+            "Signatures seen by every endpoint that predate the run.",
+        );
+        let _ = writeln!(
+            out,
+            "geyserbench_signatures_backfilled_total {}",
+            self.backfilled.load(Ordering::Relaxed)
+        );
+        if let Some(target) = self.target {
+            write_header(
+                &mut out,
+                "geyserbench_signatures_target",
+                "gauge",
+                "Signatures to compare before the run stops.",
+            );
+            let _ = writeln!(out, "geyserbench_signatures_target {}", target);
+        }
+
+        let counters: [(&str, &str, CounterFn); 7] = [
+            (
+                "geyserbench_endpoint_signatures_total",
+                "Matching signatures recorded by the endpoint, including duplicates.",
+                |m| m.signatures.load(Ordering::Relaxed),
+            ),
+            (
+                "geyserbench_endpoint_observations_total",
+                "Compared signatures the endpoint delivered within the late threshold.",
+                |m| m.observations.load(Ordering::Relaxed),
+            ),
+            (
+                "geyserbench_endpoint_first_detections_total",
+                "Compared signatures the endpoint delivered first.",
+                |m| m.first_detections.load(Ordering::Relaxed),
+            ),
+            (
+                "geyserbench_endpoint_late_total",
+                "Compared signatures the endpoint delivered after the late threshold.",
+                |m| m.late.load(Ordering::Relaxed),
+            ),
+            (
+                "geyserbench_endpoint_backfill_total",
+                "Backfilled signatures the endpoint delivered.",
+                |m| m.backfill.load(Ordering::Relaxed),
+            ),
+            (
+                "geyserbench_stream_messages_total",
+                "Stream messages received.",
+                |m| m.messages.load(Ordering::Relaxed),
+            ),
+            (
+                "geyserbench_stream_errors_total",
+                "Stream messages that arrived as errors.",
+                |m| m.stream_errors.load(Ordering::Relaxed),
+            ),
+        ];
+        for (name, help, value) in counters {
+            write_header(&mut out, name, "counter", help);
+            for (endpoint, metrics) in &self.endpoints {
+                let _ = writeln!(
+                    out,
+                    "{}{{endpoint=\"{}\"}} {}",
+                    name,
+                    escape_label(endpoint),
+                    value(metrics)
+                );
+            }
+        }
+
+        write_header(
+            &mut out,
+            "geyserbench_stream_up",
+            "gauge",
+            "1 while the endpoint's stream is open.",
+        );
+        for (endpoint, metrics) in &self.endpoints {
+            let _ = writeln!(
+                out,
+                "geyserbench_stream_up{{endpoint=\"{}\"}} {}",
+                escape_label(endpoint),
+                u8::from(metrics.connected.load(Ordering::Relaxed))
+            );
+        }
+
+        write_header(
+            &mut out,
+            "geyserbench_stream_last_message_age_seconds",
+            "gauge",
+            "Seconds since the endpoint's last stream message.",
+        );
+        for (endpoint, metrics) in &self.endpoints {
+            let last = metrics.last_message_ms.load(Ordering::Relaxed);
+            if last == 0 {
+                continue;
+            }
+            let _ = writeln!(
+                out,
+                "geyserbench_stream_last_message_age_seconds{{endpoint=\"{}\"}} {:.3}",
+                escape_label(endpoint),
+                now_ms.saturating_sub(last) as f64 / 1_000.0
+            );
+        }
+
+        write_header(
+            &mut out,
+            "geyserbench_delay_to_first_ms",
+            "histogram",
+            "Delay behind the first endpoint to deliver each compared signature.",
+        );
+        for (endpoint, metrics) in &self.endpoints {
+            let label = escape_label(endpoint);
+            let mut cumulative = 0;
+            for (bound, bucket) in DELAY_BUCKETS_MS.iter().zip(&metrics.delay.buckets) {
+                cumulative += bucket.load(Ordering::Relaxed);
+                let _ = writeln!(
+                    out,
+                    "geyserbench_delay_to_first_ms_bucket{{endpoint=\"{}\",le=\"{}\"}} {}",
+                    label, bound, cumulative
+                );
+            }
+            let count = metrics.delay.count.load(Ordering::Relaxed);
+            let _ = writeln!(
+                out,
+                "geyserbench_delay_to_first_ms_bucket{{endpoint=\"{}\",le=\"+Inf\"}} {}",
+                label, count
+            );
+            let _ = writeln!(
+                out,
+                "geyserbench_delay_to_first_ms_sum{{endpoint=\"{}\"}} {:.3}",
+                label,
+                metrics.delay.sum_us.load(Ordering::Relaxed) as f64 / 1_000.0
+            );
+            let _ = writeln!(
+                out,
+                "geyserbench_delay_to_first_ms_count{{endpoint=\"{}\"}} {}",
+                label, count
+            );
+        }
+
+        out
+    }
+}
+
+fn write_header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Binds `listen` and serves `/metrics` in the background.
+pub async fn serve(listen: &str, metrics: Arc<LiveMetrics>) -> Result<SocketAddr> {
+    let listener = TcpListener::bind(listen)
+        .await
+        .with_context(|| format!("Failed to bind Prometheus listener on {}", listen))?;
+    let addr = listener.local_addr()?;
+    info!(%addr, "Serving Prometheus metrics on /metrics");
+
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((socket, peer)) => {
+                    let metrics = metrics.clone();
+                    tokio::spawn(async move {
+                        if let Err(err) = handle_connection(socket, &metrics).await {
+                            debug!(%peer, error = %err, "Prometheus scrape failed");
+                        }
+                    });
+                }
+                Err(err) => warn!(error = %err, "Failed to accept Prometheus connection"),
+            }
+        }
+    });
+    Ok(addr)
+}
+
+async fn handle_connection(mut socket: TcpStream, metrics: &LiveMetrics) -> std::io::Result<()> {
+    let mut request = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 1024];
+    let read_request = async {
+        while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+            let read = socket.read(&mut chunk).await?;
+            if read == 0 || request.len() + read > MAX_REQUEST_BYTES {
+                break;
+            }
+            request.extend_from_slice(&chunk[..read]);
+        }
+        Ok::<_, std::io::Error>(())
+    };
+    tokio::time::timeout(REQUEST_READ_TIMEOUT, read_request)
+        .await
+        .map_err(|_| {
+            std::io::Error::new(std::io::ErrorKind::TimedOut, "request read timed out")
+        })??;
+
+    let request_line = request.split(|byte| *byte == b'\n').next().unwrap_or(&[]);
+    let mut parts = request_line.split(|byte| *byte == b' ');
+    let method = parts.next().unwrap_or(&[]);
+    let path = parts.next().unwrap_or(&[]);
+
+    let (status, content_type, body) = if method == b"GET" && path == b"/metrics" {
+        (
+            "200 OK",
+            "text/plain; version=0.0.4; charset=utf-8",
+            metrics.render(),
+        )
+    } else {
+        (
+            "404 Not Found",
+            "text/plain; charset=utf-8",
+            "Not found\n".to_string(),
+        )
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    socket.write_all(response.as_bytes()).await?;
+    socket.shutdown().await
+}
@@ -1,21 +1,16 @@
-use std::{collections::HashMap, error::Error, sync::atomic::Ordering};
+use std::{collections::HashMap, error::Error};
 
 use futures::{SinkExt, channel::mpsc::unbounded};
 use futures_util::stream::StreamExt;
 use tokio::task;
-use tracing::{Level, info};
+use tracing::info;
 
-use crate::{
-    config::{Config, Endpoint},
-    utils::{TransactionData, get_current_timestamp, open_log_file, write_log_entry},
-};
+use crate::config::{Config, Endpoint};
 
 use super::{
     GeyserProvider, ProviderContext,
-    common::{
-        TransactionAccumulator, build_signature_envelope, enqueue_signature,
-        fatal_connection_error, parse_tracked_accounts,
-    },
+    common::{SignatureRecorder, fatal_connection_error, parse_tracked_accounts, tag_transaction},
+    transport::connect_channel,
 };
 
 #[allow(clippy::all, dead_code)]
@@ -46,37 +41,9 @@ async fn process_arpc_endpoint(
     config: Config,
     context: ProviderContext,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let ProviderContext {
-        shutdown_tx,
-        mut shutdown_rx,
-        start_wallclock_secs,
-        start_instant,
-        comparator,
-        signature_tx,
-        shared_counter,
-        shared_shutdown,
-        target_transactions,
-        total_producers,
-        progress,
-    } = context;
-    let signature_sender = signature_tx;
     let tracked_accounts = parse_tracked_accounts(&config.accounts)?;
     let endpoint_name = endpoint.name.clone();
-
-    let mut log_file = if tracing::enabled!(Level::TRACE) {
-        Some(open_log_file(&endpoint_name)?)
-    } else {
-        None
-    };
-
-    let endpoint_url = endpoint.url.clone();
-
-    info!(endpoint = %endpoint_name, url = %endpoint_url, "Connecting");
-
-    let mut client = ArpcServiceClient::connect(endpoint_url.clone())
-        .await
-        .unwrap_or_else(|err| fatal_connection_error(&endpoint_name, err));
-    info!(endpoint = %endpoint_name, "Connected");
+    let (mut recorder, mut shutdown_rx) = SignatureRecorder::new(&endpoint_name, context)?;
 
     let transactions = HashMap::from([(
         "account".to_string(),
@@ -94,11 +61,26 @@ async fn process_arpc_endpoint(
 
     let (mut subscribe_tx, subscribe_rx) = unbounded::<ArpcSubscribeRequest>();
     subscribe_tx.send(request).await?;
-    let mut stream = client.subscribe(subscribe_rx).await?.into_inner();
 
-    let mut accumulator = TransactionAccumulator::new();
-    let mut transaction_count = 0usize;
+    let mut stream = match recorder.replay_stream().await? {
+        Some(stream) => stream,
+        None => {
+            let endpoint_url = endpoint.url.clone();
+
+            info!(endpoint = %endpoint_name, url = %endpoint_url, "Connecting");
+
+            let channel = connect_channel(&endpoint_url, None, recorder.frame_tap())
+                .await
+                .unwrap_or_else(|err| fatal_connection_error(&endpoint_name, err));
+            let mut client = ArpcServiceClient::new(channel);
+            info!(endpoint = %endpoint_name, "Connected");
+            recorder.stream_connected();
 
+            client.subscribe(subscribe_rx).await?.into_inner().boxed()
+        }
+    };
+
+    recorder.stream_opened();
     loop {
         tokio::select! { biased;
             _ = shutdown_rx.recv() => {
@@ -107,71 +89,35 @@ async fn process_arpc_endpoint(
             }
 
             message = stream.next() => {
-                let Some(Ok(msg)) = message else { continue };
+                let Some(message) = message else {
+                    info!(endpoint = %endpoint_name, "Stream closed");
+                    break;
+                };
+                let Ok(msg) = message else {
+                    recorder.stream_error();
+                    continue;
+                };
+                recorder.begin_frame();
                 let Some(tx) = msg.transaction else { continue };
 
-                let has_account = tx.account_keys.iter().any(|key| {
-                    tracked_accounts
-                        .iter()
-                        .any(|account| key.as_slice() == account.as_ref())
-                });
-                if !has_account { continue }
+                let tags = tag_transaction(
+                    &tracked_accounts,
+                    &tx.account_keys,
+                    tx.instructions.iter().map(|ix| (ix.program_id_index, ix.data.as_slice())),
+                );
+                recorder.mark_filtered();
+                let Some(tags) = tags else { continue };
 
-                let wallclock = get_current_timestamp();
-                let elapsed = start_instant.elapsed();
                 let signature = tx.signatures
                     .first()
                     .map(|s| bs58::encode(s).into_string())
                     .unwrap_or_default();
 
-                if let Some(file) = log_file.as_mut() {
-                    write_log_entry(file, wallclock, &endpoint_name, &signature)?;
-                }
-
-                let tx_data = TransactionData {
-                    wallclock_secs: wallclock,
-                    elapsed_since_start: elapsed,
-                    start_wallclock_secs,
-                };
-
-                let updated = accumulator.record(signature.clone(), tx_data.clone());
-
-                if updated && let Some(envelope) = build_signature_envelope(
-                    &comparator,
-                    &endpoint_name,
-                    &signature,
-                    tx_data,
-                    total_producers,
-                ) {
-                    if let Some(target) = target_transactions {
-                        let shared = shared_counter.fetch_add(1, Ordering::AcqRel) + 1;
-                        if let Some(tracker) = progress.as_ref() {
-                            tracker.record(shared);
-                        }
-                        if shared >= target && !shared_shutdown.swap(true, Ordering::AcqRel) {
-                            info!(endpoint = %endpoint_name, target, "Reached shared signature target; broadcasting shutdown");
-                            let _ = shutdown_tx.send(());
-                        }
-                    }
-
-                    if let Some(sender) = signature_sender.as_ref() {
-                        enqueue_signature(sender, &endpoint_name, &signature, envelope);
-                    }
-                }
-
-                transaction_count += 1;
+                recorder.record(signature, Some(tx.slot), tags)?;
             }
         }
     }
 
-    let unique_signatures = accumulator.len();
-    let collected = accumulator.into_inner();
-    comparator.add_batch(&endpoint_name, collected);
-    info!(
-        endpoint = %endpoint_name,
-        total_transactions = transaction_count,
-        unique_signatures,
-        "Stream closed after dispatching transactions"
-    );
+    recorder.finish();
     Ok(())
 }
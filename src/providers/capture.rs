@@ -0,0 +1,272 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use futures::{Stream, stream};
+use prost::Message;
+use tokio::io::{AsyncRead, AsyncReadExt, BufReader};
+use tonic::Status;
+use tracing::warn;
+
+pub const CAPTURE_EXTENSION: &str = "gbcap";
+const CAPTURE_BUFFER_BYTES: usize = 256 * 1024;
+/// Largest record accepted when reading a capture: tonic's default 4 MiB
+/// message limit plus room for the frame's own fields.
+const MAX_RECORD_BYTES: u64 = 5 * 1024 * 1024;
+
+/// First record of every capture file.
+#[derive(Clone, PartialEq, Message)]
+pub struct CaptureHeader {
+    #[prost(string, tag = "1")]
+    pub endpoint: String,
+    #[prost(string, tag = "2")]
+    pub kind: String,
+    /// Wall-clock start of the recorded run, in seconds since the Unix epoch.
+    #[prost(double, tag = "3")]
+    pub started_at_secs: f64,
+    #[prost(string, repeated, tag = "4")]
+    pub accounts: Vec<String>,
+}
+
+/// One stream message exactly as it came off the wire.
+#[derive(Clone, PartialEq, Message)]
+pub struct RecordedFrame {
+    /// Socket receive time when `socket_timestamp` is set, otherwise the time
+    /// the provider picked the message up.
+    #[prost(double, tag = "1")]
+    pub received_at_secs: f64,
+    /// Undecoded gRPC message bytes.
+    #[prost(bytes = "bytes", tag = "2")]
+    pub payload: Bytes,
+    /// Arrival time recorded for each signature matched in this message, in
+    /// the order the provider reported them.
+    #[prost(double, repeated, tag = "3")]
+    pub recorded_at_secs: Vec<f64>,
+    #[prost(bool, tag = "4")]
+    pub socket_timestamp: bool,
+}
+
+pub type FrameStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+/// Appends length-delimited frames to `<dir>/<endpoint>.gbcap`.
+pub struct CaptureWriter {
+    writer: BufWriter<File>,
+    path: PathBuf,
+}
+
+impl CaptureWriter {
+    pub fn create(dir: &Path, header: &CaptureHeader) -> io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!(
+            "{}.{}",
+            sanitize_file_stem(&header.endpoint),
+            CAPTURE_EXTENSION
+        ));
+        let mut writer = BufWriter::with_capacity(CAPTURE_BUFFER_BYTES, File::create(&path)?);
+        writer.write_all(&header.encode_length_delimited_to_vec())?;
+        Ok(Self { writer, path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn write_frame(&mut self, frame: &RecordedFrame) -> io::Result<()> {
+        self.writer
+            .write_all(&frame.encode_length_delimited_to_vec())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+fn sanitize_file_stem(name: &str) -> String {
+    name.chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayPacing {
+    /// Sleep between frames to reproduce the recorded inter-arrival times.
+    Original,
+    /// Feed frames back-to-back.
+    Fast,
+}
+
+/// Capture file to feed through a provider instead of a live connection.
+#[derive(Debug, Clone)]
+pub struct ReplaySource {
+    pub path: PathBuf,
+    pub pacing: ReplayPacing,
+    /// Recorded wall-clock time that maps to `started`.
+    pub origin_secs: f64,
+    pub started: Instant,
+}
+
+/// Recorded times of one replayed frame.
+#[derive(Debug, Default)]
+pub struct ReplayedFrame {
+    pub received_at_secs: f64,
+    pub socket_timestamp: bool,
+    recorded_at_secs: VecDeque<f64>,
+}
+
+impl ReplayedFrame {
+    /// Arrival time the original run recorded for the next signature of this
+    /// frame. Captures without per-signature times fall back to the frame's
+    /// receive time.
+    pub fn next_recorded_at(&mut self) -> f64 {
+        self.recorded_at_secs
+            .pop_front()
+            .unwrap_or(self.received_at_secs)
+    }
+}
+
+/// Recorded times of the frame most recently yielded by a replay stream.
+#[derive(Debug, Default)]
+pub struct ReplayClock {
+    frame: Mutex<ReplayedFrame>,
+}
+
+impl ReplayClock {
+    fn set(&self, frame: ReplayedFrame) {
+        if let Ok(mut current) = self.frame.lock() {
+            *current = frame;
+        }
+    }
+
+    pub fn take(&self) -> ReplayedFrame {
+        self.frame
+            .lock()
+            .map(|mut current| std::mem::take(&mut *current))
+            .unwrap_or_default()
+    }
+}
+
+/// Lists the capture files in `dir` with their headers, sorted by file name.
+pub async fn discover(dir: &Path) -> io::Result<Vec<(PathBuf, CaptureHeader)>> {
+    let mut entries = tokio::fs::read_dir(dir).await?;
+    let mut captures = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(CAPTURE_EXTENSION) {
+            continue;
+        }
+        let (_, header) = open_capture(&path).await?;
+        captures.push((path, header));
+    }
+    captures.sort_by(|lhs, rhs| lhs.0.cmp(&rhs.0));
+    Ok(captures)
+}
+
+async fn open_capture(path: &Path) -> io::Result<(BufReader<tokio::fs::File>, CaptureHeader)> {
+    let mut reader = BufReader::new(tokio::fs::File::open(path).await?);
+    let bytes = read_delimited(&mut reader).await?.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("{} is empty", path.display()),
+        )
+    })?;
+    let header = CaptureHeader::decode(bytes.as_slice())
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    Ok((reader, header))
+}
+
+async fn read_delimited<R: AsyncRead + Unpin>(reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut len = 0u64;
+    let mut shift = 0;
+    loop {
+        let byte = match reader.read_u8().await {
+            Ok(byte) => byte,
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof && shift == 0 => {
+                return Ok(None);
+            }
+            Err(err) => return Err(err),
+        };
+        len |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+        if shift > 63 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "frame length varint overflow",
+            ));
+        }
+    }
+
+    if len > MAX_RECORD_BYTES {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame length {len} exceeds {MAX_RECORD_BYTES} bytes"),
+        ));
+    }
+    let mut buffer = vec![0u8; len as usize];
+    reader.read_exact(&mut buffer).await?;
+    Ok(Some(buffer))
+}
+
+/// Decodes a capture file back into the provider's stream message type,
+/// stamping `clock` with each frame's recorded times before yielding it.
+pub async fn open_replay<T>(
+    source: &ReplaySource,
+    clock: Arc<ReplayClock>,
+) -> io::Result<FrameStream<T>>
+where
+    T: Message + Default + Send + 'static,
+{
+    let (reader, _) = open_capture(&source.path).await?;
+    let state = (reader, source.clone(), clock);
+
+    Ok(Box::pin(stream::unfold(
+        state,
+        |(mut reader, source, clock)| async move {
+            let bytes = match read_delimited(&mut reader).await {
+                Ok(Some(bytes)) => bytes,
+                Ok(None) => return None,
+                Err(err) => {
+                    warn!(path = %source.path.display(), error = %err, "Truncated capture; ending replay");
+                    return None;
+                }
+            };
+            let frame = match RecordedFrame::decode(bytes.as_slice()) {
+                Ok(frame) => frame,
+                Err(err) => {
+                    warn!(path = %source.path.display(), error = %err, "Corrupt capture frame; ending replay");
+                    return None;
+                }
+            };
+
+            if source.pacing == ReplayPacing::Original {
+                let offset = (frame.received_at_secs - source.origin_secs).max(0.0);
+                tokio::time::sleep_until((source.started + Duration::from_secs_f64(offset)).into())
+                    .await;
+            }
+
+            let message =
+                T::decode(frame.payload).map_err(|err| Status::data_loss(err.to_string()));
+            clock.set(ReplayedFrame {
+                received_at_secs: frame.received_at_secs,
+                socket_timestamp: frame.socket_timestamp,
+                recorded_at_secs: frame.recorded_at_secs.into(),
+            });
+            Some((message, (reader, source, clock)))
+        },
+    )))
+}
@@ -1,14 +1,38 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs::File,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
 
 use crossbeam_queue::ArrayQueue;
-use tracing::{error, warn};
+use tokio::sync::broadcast;
+use tracing::{Level, error, info, warn};
 
 use crate::{
     backend::{SignatureEnvelope, SignatureObservation},
-    utils::{Comparator, TransactionData},
+    overhead::{ClientOverhead, FrameTimer, StageHistograms},
+    prometheus::{EndpointMetrics, LiveMetrics},
+    utils::{
+        Comparator, DrainGate, ProgressTracker, StartBarrier, TransactionData, TransactionTags,
+        get_current_timestamp, open_log_file, write_log_entry,
+    },
 };
 use solana_pubkey::Pubkey;
 
+use super::{
+    ProviderContext,
+    capture::{
+        CaptureWriter, FrameStream, RecordedFrame, ReplayClock, ReplaySource, ReplayedFrame,
+        open_replay,
+    },
+    transport::FrameTap,
+};
+
 #[derive(Default)]
 pub struct TransactionAccumulator {
     entries: HashMap<String, TransactionData>,
@@ -29,14 +53,7 @@ impl TransactionAccumulator {
                 entry.insert(data);
                 true
             }
-            Entry::Occupied(mut entry) => {
-                if data.elapsed_since_start < entry.get().elapsed_since_start {
-                    entry.insert(data);
-                    true
-                } else {
-                    false
-                }
-            }
+            Entry::Occupied(mut entry) => entry.get_mut().merge(data),
         }
     }
 
@@ -49,12 +66,451 @@ impl TransactionAccumulator {
     }
 }
 
+/// Bookkeeping done once per signature when it becomes final, either because
+/// every endpoint delivered it or because the late threshold expired.
+#[derive(Clone)]
+pub struct SignatureCompletion {
+    pub shutdown_tx: broadcast::Sender<()>,
+    pub shared_counter: Arc<AtomicUsize>,
+    pub shared_shutdown: Arc<AtomicBool>,
+    pub target_transactions: Option<usize>,
+    pub progress: Option<Arc<ProgressTracker>>,
+    pub metrics: Option<Arc<LiveMetrics>>,
+    pub drain: Option<Arc<DrainGate>>,
+    pub barrier: Arc<StartBarrier>,
+}
+
+impl SignatureCompletion {
+    /// Updates live metrics and the shared signature target. `source` names
+    /// whoever finalised the signature, for logging. Returns `false` for
+    /// signatures first seen before counting started, which are left out.
+    pub fn complete(&self, source: &str, observations: &HashMap<String, TransactionData>) -> bool {
+        if !self.barrier.counts(observations) {
+            return false;
+        }
+        if let Some(metrics) = self.metrics.as_ref() {
+            metrics.record_signature(observations);
+        }
+
+        let Some(target) = self.target_transactions else {
+            return true;
+        };
+        let shared = self.shared_counter.fetch_add(1, Ordering::AcqRel) + 1;
+        if let Some(tracker) = self.progress.as_ref() {
+            tracker.record(shared);
+        }
+        if shared >= target && !self.shared_shutdown.swap(true, Ordering::AcqRel) {
+            match self.drain.as_ref() {
+                Some(drain) => {
+                    drain.begin();
+                    info!(endpoint = %source, target, "Reached shared signature target; draining slower endpoints");
+                }
+                None => {
+                    info!(endpoint = %source, target, "Reached shared signature target; broadcasting shutdown");
+                    let _ = self.shutdown_tx.send(());
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Per-endpoint hot path shared by every provider: timestamps each matched
+/// signature, records it locally and in the comparator, and drives the shared
+/// signature target and backend queue.
+pub struct SignatureRecorder {
+    endpoint_name: String,
+    start_wallclock_secs: f64,
+    start_instant: Instant,
+    comparator: Arc<Comparator>,
+    signature_sender: Option<Arc<ArrayQueue<SignatureEnvelope>>>,
+    total_producers: usize,
+    completion: SignatureCompletion,
+    frame_tap: Option<Arc<FrameTap>>,
+    overhead: Arc<ClientOverhead>,
+    stages: StageHistograms,
+    frame: Option<FrameTimer>,
+    capture: Option<CaptureWriter>,
+    /// Current message's capture record, written once the next message
+    /// arrives so it holds every signature's recorded arrival time.
+    pending_capture: Option<RecordedFrame>,
+    replay: Option<ReplaySource>,
+    replay_clock: Option<Arc<ReplayClock>>,
+    replayed: Option<ReplayedFrame>,
+    endpoint_metrics: Option<Arc<EndpointMetrics>>,
+    continuous: bool,
+    barrier: Arc<StartBarrier>,
+    drain: Option<Arc<DrainGate>>,
+    accumulator: TransactionAccumulator,
+    log_file: Option<File>,
+    transaction_count: usize,
+}
+
+impl SignatureRecorder {
+    pub fn new(
+        endpoint_name: &str,
+        context: ProviderContext,
+    ) -> std::io::Result<(Self, broadcast::Receiver<()>)> {
+        let ProviderContext {
+            shutdown_tx,
+            shutdown_rx,
+            start_wallclock_secs,
+            start_instant,
+            comparator,
+            signature_tx,
+            shared_counter,
+            shared_shutdown,
+            target_transactions,
+            total_producers,
+            progress,
+            frame_tap,
+            overhead,
+            capture,
+            replay,
+            metrics,
+            continuous,
+            barrier,
+            drain,
+        } = context;
+
+        let log_file = if tracing::enabled!(Level::TRACE) {
+            Some(open_log_file(endpoint_name)?)
+        } else {
+            None
+        };
+
+        let endpoint_metrics = metrics
+            .as_ref()
+            .and_then(|metrics| metrics.endpoint(endpoint_name));
+        let completion = SignatureCompletion {
+            shutdown_tx,
+            shared_counter,
+            shared_shutdown,
+            target_transactions,
+            progress,
+            metrics,
+            drain: drain.clone(),
+            barrier: barrier.clone(),
+        };
+
+        let recorder = Self {
+            endpoint_name: endpoint_name.to_owned(),
+            start_wallclock_secs,
+            start_instant,
+            comparator,
+            signature_sender: signature_tx,
+            total_producers,
+            completion,
+            frame_tap,
+            overhead,
+            stages: StageHistograms::default(),
+            frame: None,
+            capture,
+            pending_capture: None,
+            replay,
+            replay_clock: None,
+            replayed: None,
+            endpoint_metrics,
+            continuous,
+            barrier,
+            drain,
+            accumulator: TransactionAccumulator::new(),
+            log_file,
+            transaction_count: 0,
+        };
+
+        Ok((recorder, shutdown_rx))
+    }
+
+    /// Tap to hand to the transport so frames can be stamped and captured on
+    /// receipt.
+    pub fn frame_tap(&self) -> Option<Arc<FrameTap>> {
+        self.frame_tap.clone()
+    }
+
+    /// Opens the capture file to replay when this endpoint runs offline.
+    /// Returns `None` when the provider should connect to its live endpoint.
+    pub async fn replay_stream<T>(&mut self) -> std::io::Result<Option<FrameStream<T>>>
+    where
+        T: prost::Message + Default + Send + 'static,
+    {
+        let Some(source) = self.replay.take() else {
+            return Ok(None);
+        };
+        info!(endpoint = %self.endpoint_name, path = %source.path.display(), "Replaying capture");
+        let clock = Arc::new(ReplayClock::default());
+        self.replay_clock = Some(clock.clone());
+        open_replay(&source, clock).await.map(Some)
+    }
+
+    /// Marks the endpoint's transport as connected.
+    pub fn stream_connected(&self) {
+        self.barrier.connected(&self.endpoint_name);
+    }
+
+    /// Marks the endpoint's stream as open. Call once the subscription is live.
+    pub fn stream_opened(&self) {
+        self.barrier.subscribed(&self.endpoint_name);
+        if let Some(metrics) = self.endpoint_metrics.as_ref() {
+            metrics.stream_opened();
+        }
+    }
+
+    /// Counts a stream message that arrived as an error.
+    pub fn stream_error(&self) {
+        if let Some(metrics) = self.endpoint_metrics.as_ref() {
+            metrics.stream_error();
+        }
+    }
+
+    /// Starts timing a freshly received stream message and claims its
+    /// transport receive timestamp and, when recording, its raw bytes. Call
+    /// once per message, before decoding or filtering it.
+    pub fn begin_frame(&mut self) {
+        if let Some(frame) = self.frame.take() {
+            frame.flush(&mut self.stages);
+        }
+        self.write_pending_capture();
+        if let Some(metrics) = self.endpoint_metrics.as_ref() {
+            metrics.message_received();
+        }
+        if let Some(clock) = self.replay_clock.as_ref() {
+            self.replayed = Some(clock.take());
+            self.frame = Some(FrameTimer::start(None));
+            return;
+        }
+        let tapped = self
+            .frame_tap
+            .as_ref()
+            .map(|tap| (tap.timestamps(), tap.take().unwrap_or_default()));
+        let received_at = tapped.as_ref().and_then(|(_, message)| message.received_at);
+        if tapped
+            .as_ref()
+            .is_some_and(|(timestamps, _)| *timestamps && received_at.is_none())
+        {
+            self.stages.count_untimed_frame();
+        }
+        if self.capture.is_some()
+            && let Some(payload) = tapped.and_then(|(_, message)| message.payload)
+        {
+            self.pending_capture = Some(RecordedFrame {
+                received_at_secs: received_at.unwrap_or_else(get_current_timestamp),
+                payload,
+                recorded_at_secs: Vec::new(),
+                socket_timestamp: received_at.is_some(),
+            });
+        }
+        self.frame = Some(FrameTimer::start(received_at));
+    }
+
+    fn write_pending_capture(&mut self) {
+        let (Some(frame), Some(writer)) = (self.pending_capture.take(), self.capture.as_mut())
+        else {
+            return;
+        };
+        if let Err(err) = writer.write_frame(&frame) {
+            warn!(endpoint = %self.endpoint_name, error = %err, "Failed to write capture frame; recording stopped");
+            self.capture = None;
+        }
+    }
+
+    pub fn mark_decoded(&mut self) {
+        if let Some(frame) = self.frame.as_mut() {
+            frame.mark_decoded();
+        }
+    }
+
+    pub fn mark_filtered(&mut self) {
+        if let Some(frame) = self.frame.as_mut() {
+            frame.mark_filtered();
+        }
+    }
+
+    pub fn record(
+        &mut self,
+        signature: String,
+        slot: Option<u64>,
+        tags: TransactionTags,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        // Replayed frames keep the arrival time recorded in the original run
+        // so analysis matches it regardless of replay pacing. Every call takes
+        // a timestamp, even for signatures dropped while draining, so replay consumes
+        // the same sequence the capture recorded.
+        let (wallclock, elapsed, receive_wallclock_secs) = match self.replayed.as_mut() {
+            Some(replayed) => {
+                let recorded = replayed.next_recorded_at();
+                (
+                    recorded,
+                    Duration::from_secs_f64((recorded - self.start_wallclock_secs).max(0.0)),
+                    replayed
+                        .socket_timestamp
+                        .then_some(replayed.received_at_secs),
+                )
+            }
+            None => (
+                get_current_timestamp(),
+                self.start_instant.elapsed(),
+                self.frame.as_ref().and_then(|frame| frame.received_at),
+            ),
+        };
+        if let Some(frame) = self.pending_capture.as_mut() {
+            frame.recorded_at_secs.push(wallclock);
+        }
+
+        // While draining, only signatures another endpoint already reported count.
+        if self.drain.as_ref().is_some_and(|drain| drain.is_draining())
+            && !self.comparator.contains(&signature)
+        {
+            return Ok(());
+        }
+        let record_started = Instant::now();
+
+        if let Some(file) = self.log_file.as_mut() {
+            write_log_entry(file, wallclock, &self.endpoint_name, &signature)?;
+        }
+
+        let tx_data = TransactionData {
+            wallclock_secs: wallclock,
+            elapsed_since_start: elapsed,
+            start_wallclock_secs: self.start_wallclock_secs,
+            receive_wallclock_secs,
+            slot,
+            tags,
+        };
+
+        let updated =
+            self.continuous || self.accumulator.record(signature.clone(), tx_data.clone());
+        if let Some(metrics) = self.endpoint_metrics.as_ref() {
+            metrics.signature_recorded();
+        }
+
+        if updated
+            && let Some(observations) = self.comparator.record_observation(
+                &self.endpoint_name,
+                &signature,
+                tx_data,
+                self.total_producers,
+            )
+            && self.completion.complete(&self.endpoint_name, &observations)
+            && let Some(sender) = self.signature_sender.as_ref()
+        {
+            let envelope = build_signature_envelope(&signature, observations);
+            enqueue_signature(sender, &self.endpoint_name, &signature, envelope);
+        }
+
+        self.transaction_count += 1;
+        self.stages.record_record(record_started.elapsed());
+        if let Some(frame) = self.frame.as_mut() {
+            frame.resume();
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) {
+        if let Some(frame) = self.frame.take() {
+            frame.flush(&mut self.stages);
+        }
+        self.overhead.merge(&self.stages);
+        if let Some(metrics) = self.endpoint_metrics.as_ref() {
+            metrics.stream_closed();
+        }
+        self.write_pending_capture();
+        if let Some(mut writer) = self.capture.take() {
+            match writer.flush() {
+                Ok(()) => {
+                    info!(endpoint = %self.endpoint_name, path = %writer.path().display(), "Capture written")
+                }
+                Err(err) => {
+                    warn!(endpoint = %self.endpoint_name, error = %err, "Failed to flush capture")
+                }
+            }
+        }
+
+        let unique_signatures = self.accumulator.len();
+        let collected = self.accumulator.into_inner();
+        self.comparator.add_batch(&self.endpoint_name, collected);
+        info!(
+            endpoint = %self.endpoint_name,
+            total_transactions = self.transaction_count,
+            unique_signatures,
+            "Stream closed after dispatching transactions"
+        );
+    }
+}
+
 pub fn parse_tracked_accounts(
     accounts: &[String],
 ) -> Result<Vec<Pubkey>, solana_pubkey::ParsePubkeyError> {
     accounts.iter().map(|value| value.parse()).collect()
 }
 
+const COMPUTE_BUDGET_PROGRAM: Pubkey =
+    Pubkey::from_str_const("ComputeBudget111111111111111111111111111111");
+/// Discriminator of `ComputeBudgetInstruction::SetComputeUnitPrice`.
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+const JITO_TIP_ACCOUNTS: [Pubkey; 8] = [
+    Pubkey::from_str_const("96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5"),
+    Pubkey::from_str_const("HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe"),
+    Pubkey::from_str_const("Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY"),
+    Pubkey::from_str_const("ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49"),
+    Pubkey::from_str_const("DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh"),
+    Pubkey::from_str_const("ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt"),
+    Pubkey::from_str_const("DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL"),
+    Pubkey::from_str_const("3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT"),
+];
+
+/// Tags a transaction from its account keys and its instructions, given as
+/// `program_id_index` and data. Returns `None` when it references no tracked
+/// account. Execution status is left for the caller to fill in.
+pub fn tag_transaction<'a, K: AsRef<[u8]>>(
+    tracked_accounts: &[Pubkey],
+    account_keys: &[K],
+    instructions: impl IntoIterator<Item = (u32, &'a [u8])>,
+) -> Option<TransactionTags> {
+    let accounts: Vec<String> = tracked_accounts
+        .iter()
+        .filter(|account| {
+            account_keys
+                .iter()
+                .any(|key| key.as_ref() == account.as_ref())
+        })
+        .map(|account| account.to_string())
+        .collect();
+    if accounts.is_empty() {
+        return None;
+    }
+
+    let mut programs: Vec<String> = Vec::new();
+    let mut cu_price = None;
+    for (index, data) in instructions {
+        let Some(key) = account_keys.get(index as usize) else {
+            continue;
+        };
+        if key.as_ref() == COMPUTE_BUDGET_PROGRAM.as_ref()
+            && data.first() == Some(&SET_COMPUTE_UNIT_PRICE)
+            && let Some(price) = data.get(1..9)
+        {
+            cu_price = price.try_into().ok().map(u64::from_le_bytes);
+        }
+        let program = bs58::encode(key.as_ref()).into_string();
+        if !programs.contains(&program) {
+            programs.push(program);
+        }
+    }
+    let jito_tip = account_keys.iter().any(|key| {
+        JITO_TIP_ACCOUNTS
+            .iter()
+            .any(|tip| key.as_ref() == tip.as_ref())
+    });
+    Some(TransactionTags {
+        accounts,
+        programs,
+        succeeded: None,
+        cu_price,
+        jito_tip,
+    })
+}
+
 pub fn fatal_connection_error(endpoint: &str, err: impl std::fmt::Display) -> ! {
     error!(endpoint = endpoint, error = %err, "Failed to connect to endpoint");
     eprintln!("Failed to connect to endpoint {}: {}", endpoint, err);
@@ -62,29 +518,22 @@ pub fn fatal_connection_error(endpoint: &str, err: impl std::fmt::Display) -> !
 }
 
 pub fn build_signature_envelope(
-    comparator: &Arc<Comparator>,
-    endpoint: &str,
     signature: &str,
-    data: TransactionData,
-    total_producers: usize,
-) -> Option<SignatureEnvelope> {
-    comparator
-        .record_observation(endpoint, signature, data, total_producers)
-        .map(|observations| {
-            let mut payload = observations
-                .into_iter()
-                .map(|(endpoint, tx_data)| SignatureObservation {
-                    endpoint,
-                    timestamp: tx_data.wallclock_secs,
-                    backfilled: tx_data.wallclock_secs < tx_data.start_wallclock_secs,
-                })
-                .collect::<Vec<_>>();
-            payload.sort_by(|lhs, rhs| lhs.endpoint.cmp(&rhs.endpoint));
-            SignatureEnvelope {
-                signature: signature.to_owned(),
-                observations: payload,
-            }
+    observations: HashMap<String, TransactionData>,
+) -> SignatureEnvelope {
+    let mut payload = observations
+        .into_iter()
+        .map(|(endpoint, tx_data)| SignatureObservation {
+            endpoint,
+            timestamp: tx_data.wallclock_secs,
+            backfilled: tx_data.wallclock_secs < tx_data.start_wallclock_secs,
         })
+        .collect::<Vec<_>>();
+    payload.sort_by(|lhs, rhs| lhs.endpoint.cmp(&rhs.endpoint));
+    SignatureEnvelope {
+        signature: signature.to_owned(),
+        observations: payload,
+    }
 }
 
 pub fn enqueue_signature(
@@ -97,3 +546,151 @@ pub fn enqueue_signature(
         warn!(endpoint = endpoint, signature = %signature, "Signature queue full; dropping observation");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIGNATURE: &str = "sig";
+    const TRACKED: &str = "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA";
+    const PROGRAM: &str = "11111111111111111111111111111111";
+
+    fn update(elapsed_ms: u64, tags: TransactionTags) -> TransactionData {
+        TransactionData {
+            wallclock_secs: 100.0 + elapsed_ms as f64 / 1_000.0,
+            elapsed_since_start: Duration::from_millis(elapsed_ms),
+            start_wallclock_secs: 100.0,
+            receive_wallclock_secs: None,
+            slot: Some(7),
+            tags,
+        }
+    }
+
+    fn account_update(elapsed_ms: u64) -> TransactionData {
+        update(
+            elapsed_ms,
+            TransactionTags {
+                accounts: vec![TRACKED.to_string()],
+                ..Default::default()
+            },
+        )
+    }
+
+    fn transaction_update(elapsed_ms: u64) -> TransactionData {
+        update(
+            elapsed_ms,
+            TransactionTags {
+                accounts: vec![TRACKED.to_string()],
+                programs: vec![PROGRAM.to_string()],
+                succeeded: Some(true),
+                cu_price: Some(5_000),
+                jito_tip: true,
+            },
+        )
+    }
+
+    fn assert_merged(data: &TransactionData) {
+        assert_eq!(data.elapsed_since_start, Duration::from_millis(10));
+        assert_eq!(data.tags.accounts, vec![TRACKED.to_string()]);
+        assert_eq!(data.tags.programs, vec![PROGRAM.to_string()]);
+        assert_eq!(data.tags.succeeded, Some(true));
+        assert_eq!(data.tags.cu_price, Some(5_000));
+        assert!(data.tags.jito_tip);
+    }
+
+    #[test]
+    fn later_transaction_update_fills_in_account_update_tags() {
+        let mut accumulator = TransactionAccumulator::new();
+        assert!(accumulator.record(SIGNATURE.to_string(), account_update(10)));
+        assert!(accumulator.record(SIGNATURE.to_string(), transaction_update(12)));
+        assert!(!accumulator.record(SIGNATURE.to_string(), transaction_update(15)));
+        assert_merged(&accumulator.into_inner()[SIGNATURE]);
+
+        let comparator = Comparator::new();
+        comparator.record_observation("yellowstone", SIGNATURE, account_update(10), 2);
+        comparator.record_observation("yellowstone", SIGNATURE, transaction_update(12), 2);
+        let observations = comparator
+            .record_observation("other", SIGNATURE, account_update(20), 2)
+            .expect("complete once both endpoints delivered");
+        assert_merged(&observations["yellowstone"]);
+    }
+
+    #[test]
+    fn earlier_update_keeps_tags_of_the_later_one() {
+        let mut accumulator = TransactionAccumulator::new();
+        accumulator.record(SIGNATURE.to_string(), transaction_update(12));
+        assert!(accumulator.record(SIGNATURE.to_string(), account_update(10)));
+        assert_merged(&accumulator.into_inner()[SIGNATURE]);
+    }
+
+    fn key(value: &str) -> Pubkey {
+        value.parse().unwrap()
+    }
+
+    fn cu_price_instruction(price: u64) -> Vec<u8> {
+        let mut data = vec![SET_COMPUTE_UNIT_PRICE];
+        data.extend_from_slice(&price.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn tags_only_transactions_referencing_tracked_accounts() {
+        let tracked = [key(TRACKED)];
+        let other = key(PROGRAM);
+        assert!(tag_transaction(&tracked, &[other], std::iter::empty()).is_none());
+
+        let tags = tag_transaction(&tracked, &[other, tracked[0]], std::iter::empty()).unwrap();
+        assert_eq!(tags.accounts, vec![TRACKED.to_string()]);
+        assert!(tags.programs.is_empty());
+        assert_eq!(tags.succeeded, None);
+        assert_eq!(tags.cu_price, None);
+        assert!(!tags.jito_tip);
+    }
+
+    #[test]
+    fn collects_each_invoked_program_once() {
+        let tracked = [key(TRACKED)];
+        let keys = [tracked[0], key(PROGRAM), COMPUTE_BUDGET_PROGRAM];
+        let instructions: [(u32, &[u8]); 4] = [(1, &[]), (2, &[0]), (1, &[1]), (9, &[])];
+        let tags = tag_transaction(&tracked, &keys, instructions).unwrap();
+        assert_eq!(
+            tags.programs,
+            vec![PROGRAM.to_string(), COMPUTE_BUDGET_PROGRAM.to_string()]
+        );
+    }
+
+    #[test]
+    fn reads_compute_unit_price_from_compute_budget_instructions() {
+        let tracked = [key(TRACKED)];
+        let keys = [tracked[0], COMPUTE_BUDGET_PROGRAM, key(PROGRAM)];
+        let price = cu_price_instruction(123_456);
+        let cu_price = |instructions: &[(u32, &[u8])]| {
+            tag_transaction(&tracked, &keys, instructions.iter().copied())
+                .unwrap()
+                .cu_price
+        };
+
+        assert_eq!(cu_price(&[(1, &price)]), Some(123_456));
+        // Other compute-budget instructions and other programs are ignored.
+        assert_eq!(cu_price(&[(1, &[2, 1, 0, 0, 0])]), None);
+        assert_eq!(cu_price(&[(2, &price)]), None);
+        // Truncated data carries no price.
+        assert_eq!(cu_price(&[(1, &price[..8])]), None);
+        assert_eq!(cu_price(&[(1, &[SET_COMPUTE_UNIT_PRICE])]), None);
+        // Trailing bytes past the eight-byte price are ignored.
+        let mut padded = price.clone();
+        padded.push(0xff);
+        assert_eq!(cu_price(&[(1, &padded)]), Some(123_456));
+    }
+
+    #[test]
+    fn flags_transactions_referencing_a_jito_tip_account() {
+        let tracked = [key(TRACKED)];
+        for tip in JITO_TIP_ACCOUNTS {
+            let tags = tag_transaction(&tracked, &[tracked[0], tip], std::iter::empty()).unwrap();
+            assert!(tags.jito_tip);
+        }
+        let tags = tag_transaction(&tracked, &[tracked[0], key(PROGRAM)], std::iter::empty());
+        assert!(!tags.unwrap().jito_tip);
+    }
+}
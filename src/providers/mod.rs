@@ -12,15 +12,19 @@ use tokio::sync::broadcast;
 use crate::{
     backend::SignatureEnvelope,
     config::{Config, Endpoint, EndpointKind},
-    utils::{Comparator, ProgressTracker},
+    overhead::ClientOverhead,
+    prometheus::LiveMetrics,
+    utils::{Comparator, DrainGate, ProgressTracker, StartBarrier},
 };
 
 pub mod arpc;
+pub mod capture;
 pub mod common;
 pub mod jetstream;
 pub mod shreder;
 pub mod shredstream;
 pub mod thor;
+pub mod transport;
 pub mod yellowstone;
 mod yellowstone_client;
 
@@ -56,4 +60,14 @@ pub struct ProviderContext {
     pub target_transactions: Option<usize>,
     pub total_producers: usize,
     pub progress: Option<Arc<ProgressTracker>>,
+    pub frame_tap: Option<Arc<transport::FrameTap>>,
+    pub overhead: Arc<ClientOverhead>,
+    pub capture: Option<capture::CaptureWriter>,
+    pub replay: Option<capture::ReplaySource>,
+    pub metrics: Option<Arc<LiveMetrics>>,
+    /// Continuous runs evict signatures from the comparator, so recorders keep
+    /// no per-endpoint batch that would re-add them on shutdown.
+    pub continuous: bool,
+    pub barrier: Arc<StartBarrier>,
+    pub drain: Option<Arc<DrainGate>>,
 }
@@ -1,22 +1,17 @@
-use std::{error::Error, sync::atomic::Ordering};
+use std::error::Error;
 
-use crate::{
-    config::{Config, Endpoint},
-    utils::{TransactionData, get_current_timestamp, open_log_file, write_log_entry},
-};
+use crate::config::{Config, Endpoint};
 use futures_util::stream::StreamExt;
 
 use prost::Message;
 use tokio::task;
-use tonic::{Request, Streaming, metadata::MetadataValue, transport::Channel};
-use tracing::{Level, info};
+use tonic::{Request, Streaming, metadata::MetadataValue};
+use tracing::info;
 
 use super::{
     GeyserProvider, ProviderContext,
-    common::{
-        TransactionAccumulator, build_signature_envelope, enqueue_signature,
-        fatal_connection_error, parse_tracked_accounts,
-    },
+    common::{SignatureRecorder, fatal_connection_error, parse_tracked_accounts, tag_transaction},
+    transport::connect_channel,
 };
 
 #[allow(clippy::all, dead_code)]
@@ -50,68 +45,52 @@ async fn process_thor_endpoint(
     config: Config,
     context: ProviderContext,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let ProviderContext {
-        shutdown_tx,
-        mut shutdown_rx,
-        start_wallclock_secs,
-        start_instant,
-        comparator,
-        signature_tx,
-        shared_counter,
-        shared_shutdown,
-        target_transactions,
-        total_producers,
-        progress,
-    } = context;
-    let signature_sender = signature_tx;
     let tracked_accounts = parse_tracked_accounts(&config.accounts)?;
     let endpoint_name = endpoint.name.clone();
+    let (mut recorder, mut shutdown_rx) = SignatureRecorder::new(&endpoint_name, context)?;
+
+    let mut stream = match recorder.replay_stream::<StreamResponse>().await? {
+        Some(stream) => stream,
+        None => {
+            let endpoint_url = endpoint.url.clone();
+            let auth_header = endpoint
+                .x_token
+                .as_ref()
+                .map(|token| token.trim())
+                .filter(|token| !token.is_empty())
+                .map(|token| {
+                    MetadataValue::try_from(token)
+                        .unwrap_or_else(|err| fatal_connection_error(&endpoint_name, err))
+                });
 
-    let mut log_file = if tracing::enabled!(Level::TRACE) {
-        Some(open_log_file(&endpoint_name)?)
-    } else {
-        None
+            info!(endpoint = %endpoint_name, url = %endpoint_url, "Connecting");
+
+            // Connect to the gRPC server
+            let uri = endpoint_url
+                .parse::<tonic::transport::Uri>()
+                .unwrap_or_else(|err| fatal_connection_error(&endpoint_name, err));
+            let channel = connect_channel(&uri.to_string(), None, recorder.frame_tap())
+                .await
+                .unwrap_or_else(|err| fatal_connection_error(&endpoint_name, err));
+            let mut publisher_client =
+                EventPublisherClient::with_interceptor(channel, move |mut req: Request<()>| {
+                    if let Some(ref token) = auth_header {
+                        req.metadata_mut().insert("authorization", token.clone());
+                    }
+                    Ok(req)
+                });
+            info!(endpoint = %endpoint_name, "Connected");
+            recorder.stream_connected();
+
+            let stream: Streaming<StreamResponse> = publisher_client
+                .subscribe_to_transactions(())
+                .await?
+                .into_inner();
+            stream.boxed()
+        }
     };
 
-    let endpoint_url = endpoint.url.clone();
-    let auth_header = endpoint
-        .x_token
-        .as_ref()
-        .map(|token| token.trim())
-        .filter(|token| !token.is_empty())
-        .map(|token| {
-            MetadataValue::try_from(token)
-                .unwrap_or_else(|err| fatal_connection_error(&endpoint_name, err))
-        });
-
-    info!(endpoint = %endpoint_name, url = %endpoint_url, "Connecting");
-
-    // Connect to the gRPC server
-    let uri = endpoint_url
-        .parse::<tonic::transport::Uri>()
-        .unwrap_or_else(|err| fatal_connection_error(&endpoint_name, err));
-    let channel = Channel::from_shared(uri.to_string())
-        .unwrap_or_else(|err| fatal_connection_error(&endpoint_name, err))
-        .connect()
-        .await
-        .unwrap_or_else(|err| fatal_connection_error(&endpoint_name, err));
-    let mut publisher_client =
-        EventPublisherClient::with_interceptor(channel, move |mut req: Request<()>| {
-            if let Some(ref token) = auth_header {
-                req.metadata_mut().insert("authorization", token.clone());
-            }
-            Ok(req)
-        });
-    info!(endpoint = %endpoint_name, "Connected");
-
-    let mut stream: Streaming<StreamResponse> = publisher_client
-        .subscribe_to_transactions(())
-        .await?
-        .into_inner();
-
-    let mut accumulator = TransactionAccumulator::new();
-    let mut transaction_count = 0usize;
-
+    recorder.stream_opened();
     loop {
         tokio::select! { biased;
             _ = shutdown_rx.recv() => {
@@ -120,82 +99,42 @@ async fn process_thor_endpoint(
             }
 
             message = stream.next() => {
-                let Some(Ok(msg)) = message else { continue };
+                let Some(message) = message else {
+                    info!(endpoint = %endpoint_name, "Stream closed");
+                    break;
+                };
+                let Ok(msg) = message else {
+                    recorder.stream_error();
+                    continue;
+                };
+                recorder.begin_frame();
                 let Ok(message_wrapper) = MessageWrapper::decode(&*msg.data) else { continue };
+                recorder.mark_decoded();
                 let Some(EventMessage::Transaction(transaction_event_wrapper)) = message_wrapper.event_message else { continue };
                 let Some(transaction_event) = transaction_event_wrapper.transaction else { continue };
                 let Some(transaction) = transaction_event.transaction.as_ref() else { continue };
                 let Some(message) = transaction.message.as_ref() else { continue };
 
-                let has_account = message.account_keys.iter().any(|key| {
-                    tracked_accounts
-                        .iter()
-                        .any(|account| key.as_slice() == account.as_ref())
-                });
-
-                if has_account {
-                    let wallclock = get_current_timestamp();
-                    let elapsed = start_instant.elapsed();
+                let tags = tag_transaction(
+                    &tracked_accounts,
+                    &message.account_keys,
+                    message.instructions.iter().map(|ix| (ix.program_id_index, ix.data.as_slice())),
+                );
+                recorder.mark_filtered();
+
+                if let Some(mut tags) = tags {
+                    tags.succeeded = transaction_event
+                        .transaction_status_meta
+                        .as_ref()
+                        .map(|meta| !meta.is_status_err);
                     let signature = bs58::encode(&transaction_event.signature).into_string();
-
-                    if let Some(file) = log_file.as_mut() {
-                        write_log_entry(file, wallclock, &endpoint_name, &signature)?;
-                    }
-
-                    let tx_data = TransactionData {
-                        wallclock_secs: wallclock,
-                        elapsed_since_start: elapsed,
-                        start_wallclock_secs,
-                    };
-
-                    let updated = accumulator.record(
-                        signature.clone(),
-                        tx_data.clone(),
-                    );
-
-                    if updated
-                        && let Some(envelope) = build_signature_envelope(
-                            &comparator,
-                            &endpoint_name,
-                            &signature,
-                            tx_data,
-                            total_producers,
-                        ) {
-                            if let Some(target) = target_transactions {
-                                let shared = shared_counter
-                                    .fetch_add(1, Ordering::AcqRel)
-                                    + 1;
-                                if let Some(tracker) = progress.as_ref() {
-                                    tracker.record(shared);
-                                }
-                                if shared >= target
-                                    && !shared_shutdown.swap(true, Ordering::AcqRel)
-                                {
-                                    info!(endpoint = %endpoint_name, target, "Reached shared signature target; broadcasting shutdown");
-                                    let _ = shutdown_tx.send(());
-                                }
-                            }
-
-                            if let Some(sender) = signature_sender.as_ref() {
-                                enqueue_signature(sender, &endpoint_name, &signature, envelope);
-                            }
-                        }
-
-                    transaction_count += 1;
+                    recorder.record(signature, Some(transaction_event.slot), tags)?;
                 }
             }
         }
     }
 
-    let unique_signatures = accumulator.len();
-    let collected = accumulator.into_inner();
-    comparator.add_batch(&endpoint_name, collected);
-    info!(
-        endpoint = %endpoint_name,
-        total_transactions = transaction_count,
-        unique_signatures,
-        "Stream closed after dispatching transactions"
-    );
+    recorder.finish();
 
     Ok(())
 }
@@ -0,0 +1,584 @@
+use std::{
+    collections::VecDeque,
+    future::Future,
+    io,
+    pin::Pin,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    task::{Context, Poll},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bytes::{Bytes, BytesMut};
+use hyper_util::rt::TokioIo;
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::TcpStream,
+};
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint, Uri};
+use tracing::debug;
+
+/// Stream messages not yet claimed by the provider. Only a few are buffered
+/// between the transport and the provider at any time.
+const MAX_PENDING_MESSAGES: usize = 4_096;
+/// gRPC length-prefixed message header: compression flag and big-endian length.
+const GRPC_HEADER_BYTES: usize = 5;
+/// Payload bytes reserved up front; a corrupt length prefix must not
+/// allocate gigabytes before any data arrives.
+const MAX_PAYLOAD_RESERVE: usize = 1 << 20;
+
+/// Time of the latest socket read on a connection, updated on every read.
+#[derive(Debug, Default)]
+struct ReceiveClock {
+    last_read_ns: AtomicU64,
+}
+
+impl ReceiveClock {
+    fn mark(&self, unix_ns: u64) {
+        self.last_read_ns.store(unix_ns.max(1), Ordering::Release);
+    }
+
+    fn last_read_ns(&self) -> u64 {
+        self.last_read_ns.load(Ordering::Acquire)
+    }
+}
+
+/// What the transport saw of one gRPC message.
+#[derive(Debug, Default)]
+pub struct TappedMessage {
+    /// Time of the socket read that delivered the message's final byte, in
+    /// seconds since the Unix epoch.
+    pub received_at: Option<f64>,
+    /// Undecoded message bytes, kept only when recording a capture.
+    pub payload: Option<Bytes>,
+}
+
+/// Per-endpoint hook into the response stream. Each gRPC message is stamped
+/// with the latest socket read when its final byte is handed to the decoder,
+/// i.e. the read that completed it, and optionally keeps its raw bytes.
+#[derive(Debug)]
+pub struct FrameTap {
+    clock: Option<Arc<ReceiveClock>>,
+    keep_payloads: bool,
+    pending: Mutex<VecDeque<TappedMessage>>,
+}
+
+impl FrameTap {
+    /// Returns `None` when neither receive timestamps nor payloads are needed.
+    pub fn new(socket_timestamps: bool, keep_payloads: bool) -> Option<Arc<Self>> {
+        (socket_timestamps || keep_payloads).then(|| {
+            Arc::new(Self {
+                clock: socket_timestamps.then(Arc::default),
+                keep_payloads,
+                pending: Mutex::new(VecDeque::new()),
+            })
+        })
+    }
+
+    /// Whether messages carry socket receive timestamps.
+    pub fn timestamps(&self) -> bool {
+        self.clock.is_some()
+    }
+
+    fn message_completed(&self, payload: Option<Bytes>) {
+        let received_at = match self.clock.as_ref().map(|clock| clock.last_read_ns()) {
+            None | Some(0) => None,
+            Some(ns) => Some(ns as f64 / 1_000_000_000.0),
+        };
+        if let Ok(mut pending) = self.pending.lock() {
+            if pending.len() == MAX_PENDING_MESSAGES {
+                pending.pop_front();
+            }
+            pending.push_back(TappedMessage {
+                received_at,
+                payload,
+            });
+        }
+    }
+
+    /// Returns the next decoded message's tap. Call once per message, in
+    /// stream order.
+    pub fn take(&self) -> Option<TappedMessage> {
+        self.pending.lock().ok()?.pop_front()
+    }
+}
+
+pub async fn connect_channel(
+    url: &str,
+    tls: Option<ClientTlsConfig>,
+    tap: Option<Arc<FrameTap>>,
+) -> Result<TimedChannel, tonic::transport::Error> {
+    let mut endpoint = Endpoint::from_shared(url.to_owned())?;
+    if let Some(tls) = tls {
+        endpoint = endpoint.tls_config(tls)?;
+    }
+    connect_endpoint(endpoint, tap).await
+}
+
+pub async fn connect_endpoint(
+    endpoint: Endpoint,
+    tap: Option<Arc<FrameTap>>,
+) -> Result<TimedChannel, tonic::transport::Error> {
+    let inner = match tap.as_ref().and_then(|tap| tap.clock.clone()) {
+        Some(clock) => {
+            endpoint
+                .tcp_nodelay(true)
+                .connect_with_connector(TimestampingConnector { clock })
+                .await?
+        }
+        None => endpoint.connect().await?,
+    };
+    Ok(TimedChannel { inner, tap })
+}
+
+/// Channel that feeds each response message through the endpoint's [`FrameTap`].
+#[derive(Clone)]
+pub struct TimedChannel {
+    inner: Channel,
+    tap: Option<Arc<FrameTap>>,
+}
+
+impl tower::Service<http::Request<tonic::body::Body>> for TimedChannel {
+    type Response = http::Response<TimedBody>;
+    type Error = tonic::transport::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<tonic::body::Body>) -> Self::Future {
+        let tap = self.tap.clone();
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            let response = response.await?;
+            Ok(response.map(|inner| TimedBody {
+                inner,
+                framer: tap.map(GrpcFramer::new),
+            }))
+        })
+    }
+}
+
+/// Response body that tracks gRPC message boundaries as data is polled.
+#[derive(Default)]
+pub struct TimedBody {
+    inner: tonic::body::Body,
+    framer: Option<GrpcFramer>,
+}
+
+impl http_body::Body for TimedBody {
+    type Data = Bytes;
+    type Error = tonic::Status;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<http_body::Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+        let polled = Pin::new(&mut this.inner).poll_frame(cx);
+        if let (Poll::Ready(Some(Ok(frame))), Some(framer)) = (&polled, this.framer.as_mut())
+            && let Some(data) = frame.data_ref()
+        {
+            framer.feed(data);
+        }
+        polled
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// Incremental parser for the gRPC length-prefixed message framing.
+struct GrpcFramer {
+    tap: Arc<FrameTap>,
+    header: [u8; GRPC_HEADER_BYTES],
+    header_len: usize,
+    remaining: usize,
+    payload: BytesMut,
+}
+
+impl GrpcFramer {
+    fn new(tap: Arc<FrameTap>) -> Self {
+        Self {
+            tap,
+            header: [0; GRPC_HEADER_BYTES],
+            header_len: 0,
+            remaining: 0,
+            payload: BytesMut::new(),
+        }
+    }
+
+    fn feed(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            if self.header_len < GRPC_HEADER_BYTES {
+                let take = (GRPC_HEADER_BYTES - self.header_len).min(data.len());
+                self.header[self.header_len..self.header_len + take].copy_from_slice(&data[..take]);
+                self.header_len += take;
+                data = &data[take..];
+                if self.header_len < GRPC_HEADER_BYTES {
+                    return;
+                }
+                let mut len = [0; 4];
+                len.copy_from_slice(&self.header[1..]);
+                self.remaining = u32::from_be_bytes(len) as usize;
+                if self.tap.keep_payloads {
+                    self.payload
+                        .reserve(self.remaining.min(MAX_PAYLOAD_RESERVE));
+                }
+            }
+
+            let take = self.remaining.min(data.len());
+            if self.tap.keep_payloads {
+                self.payload.extend_from_slice(&data[..take]);
+            }
+            self.remaining -= take;
+            data = &data[take..];
+            if self.remaining == 0 {
+                self.header_len = 0;
+                let payload = self
+                    .tap
+                    .keep_payloads
+                    .then(|| self.payload.split().freeze());
+                self.tap.message_completed(payload);
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+struct TimestampingConnector {
+    clock: Arc<ReceiveClock>,
+}
+
+impl tower::Service<Uri> for TimestampingConnector {
+    type Response = TokioIo<TimestampedTcpStream>;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = io::Result<Self::Response>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let clock = self.clock.clone();
+        Box::pin(async move {
+            let host = uri
+                .host()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "URI has no host"))?
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .to_owned();
+            let port = uri.port_u16().unwrap_or(match uri.scheme_str() {
+                Some("https") => 443,
+                _ => 80,
+            });
+
+            let stream = TcpStream::connect((host.as_str(), port)).await?;
+            stream.set_nodelay(true)?;
+            let kernel_timestamps = enable_kernel_timestamps(&stream);
+            debug!(host = %host, port, kernel_timestamps, "Opened timestamped connection");
+
+            Ok(TokioIo::new(TimestampedTcpStream {
+                inner: stream,
+                clock,
+                kernel_timestamps,
+            }))
+        })
+    }
+}
+
+/// TCP stream that updates the shared [`ReceiveClock`] on every read, using the
+/// kernel software receive timestamp when available.
+struct TimestampedTcpStream {
+    inner: TcpStream,
+    clock: Arc<ReceiveClock>,
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    kernel_timestamps: bool,
+}
+
+impl AsyncRead for TimestampedTcpStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+
+        #[cfg(target_os = "linux")]
+        if this.kernel_timestamps {
+            return poll_read_timestamped(this, cx, buf);
+        }
+
+        let filled_before = buf.filled().len();
+        let result = Pin::new(&mut this.inner).poll_read(cx, buf);
+        if matches!(result, Poll::Ready(Ok(()))) && buf.filled().len() > filled_before {
+            this.clock.mark(userspace_unix_ns());
+        }
+        result
+    }
+}
+
+impl AsyncWrite for TimestampedTcpStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+fn userspace_unix_ns() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(target_os = "linux")]
+fn enable_kernel_timestamps(stream: &TcpStream) -> bool {
+    use std::os::fd::AsRawFd;
+
+    let flags: libc::c_uint = libc::SOF_TIMESTAMPING_RX_SOFTWARE | libc::SOF_TIMESTAMPING_SOFTWARE;
+    let result = unsafe {
+        libc::setsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_TIMESTAMPING,
+            (&flags as *const libc::c_uint).cast(),
+            std::mem::size_of_val(&flags) as libc::socklen_t,
+        )
+    };
+
+    if result != 0 {
+        tracing::warn!(
+            error = %io::Error::last_os_error(),
+            "Failed to enable SO_TIMESTAMPING; falling back to userspace receive timestamps"
+        );
+        return false;
+    }
+    true
+}
+
+#[cfg(not(target_os = "linux"))]
+fn enable_kernel_timestamps(_stream: &TcpStream) -> bool {
+    false
+}
+
+#[cfg(target_os = "linux")]
+fn poll_read_timestamped(
+    stream: &mut TimestampedTcpStream,
+    cx: &mut Context<'_>,
+    buf: &mut ReadBuf<'_>,
+) -> Poll<io::Result<()>> {
+    use std::os::fd::AsRawFd;
+    use tokio::io::Interest;
+
+    let fd = stream.inner.as_raw_fd();
+    loop {
+        std::task::ready!(stream.inner.poll_read_ready(cx))?;
+
+        let unfilled = buf.initialize_unfilled();
+        match stream
+            .inner
+            .try_io(Interest::READABLE, || recv_with_timestamp(fd, unfilled))
+        {
+            Ok((read, kernel_ns)) => {
+                buf.advance(read);
+                if read > 0 {
+                    stream
+                        .clock
+                        .mark(kernel_ns.unwrap_or_else(userspace_unix_ns));
+                }
+                return Poll::Ready(Ok(()));
+            }
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => continue,
+            Err(err) => return Poll::Ready(Err(err)),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn recv_with_timestamp(fd: libc::c_int, buf: &mut [u8]) -> io::Result<(usize, Option<u64>)> {
+    // Large enough for one SCM_TIMESTAMPING control message (3 timespecs).
+    let mut control = [0u64; 16];
+    let mut iov = libc::iovec {
+        iov_base: buf.as_mut_ptr().cast(),
+        iov_len: buf.len(),
+    };
+    let mut msg: libc::msghdr = unsafe { std::mem::zeroed() };
+    msg.msg_iov = &mut iov;
+    msg.msg_iovlen = 1;
+    msg.msg_control = control.as_mut_ptr().cast();
+    msg.msg_controllen = std::mem::size_of_val(&control) as _;
+
+    let read = unsafe { libc::recvmsg(fd, &mut msg, 0) };
+    if read < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut kernel_ns = None;
+    unsafe {
+        let mut cmsg = libc::CMSG_FIRSTHDR(&msg);
+        while !cmsg.is_null() {
+            if (*cmsg).cmsg_level == libc::SOL_SOCKET && (*cmsg).cmsg_type == libc::SCM_TIMESTAMPING
+            {
+                let stamps: [libc::timespec; 3] =
+                    std::ptr::read_unaligned(libc::CMSG_DATA(cmsg).cast());
+                let software = stamps[0];
+                if software.tv_sec != 0 || software.tv_nsec != 0 {
+                    kernel_ns =
+                        Some(software.tv_sec as u64 * 1_000_000_000 + software.tv_nsec as u64);
+                }
+            }
+            cmsg = libc::CMSG_NXTHDR(&msg, cmsg);
+        }
+    }
+
+    Ok((read as usize, kernel_ns))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(compressed: bool, payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![u8::from(compressed)];
+        frame.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    fn tapped_framer(keep_payloads: bool) -> (Arc<FrameTap>, GrpcFramer) {
+        let tap = FrameTap::new(true, keep_payloads).unwrap();
+        (tap.clone(), GrpcFramer::new(tap))
+    }
+
+    /// Feeds one socket read made at `unix_secs`.
+    fn read(tap: &FrameTap, framer: &mut GrpcFramer, unix_secs: u64, data: &[u8]) {
+        tap.clock.as_ref().unwrap().mark(unix_secs * 1_000_000_000);
+        framer.feed(data);
+    }
+
+    #[test]
+    fn message_split_across_reads_is_stamped_by_the_last_one() {
+        let (tap, mut framer) = tapped_framer(true);
+        let bytes = frame(false, b"hello world");
+
+        read(&tap, &mut framer, 1, &bytes[..3]);
+        read(&tap, &mut framer, 2, &bytes[3..8]);
+        assert!(tap.take().is_none());
+        read(&tap, &mut framer, 3, &bytes[8..]);
+
+        let message = tap.take().unwrap();
+        assert_eq!(message.received_at, Some(3.0));
+        assert_eq!(message.payload.as_deref(), Some(&b"hello world"[..]));
+        assert!(tap.take().is_none());
+    }
+
+    #[test]
+    fn several_messages_in_one_read_share_its_timestamp() {
+        let (tap, mut framer) = tapped_framer(true);
+        let third = frame(false, b"third");
+        let mut bytes = frame(false, b"first");
+        bytes.extend(frame(false, b"second"));
+        bytes.extend_from_slice(&third[..2]);
+
+        read(&tap, &mut framer, 1, &bytes);
+        let first = tap.take().unwrap();
+        let second = tap.take().unwrap();
+        assert_eq!(first.payload.as_deref(), Some(&b"first"[..]));
+        assert_eq!(second.payload.as_deref(), Some(&b"second"[..]));
+        assert_eq!(
+            (first.received_at, second.received_at),
+            (Some(1.0), Some(1.0))
+        );
+        assert!(tap.take().is_none());
+
+        read(&tap, &mut framer, 2, &third[2..]);
+        let third = tap.take().unwrap();
+        assert_eq!(third.payload.as_deref(), Some(&b"third"[..]));
+        assert_eq!(third.received_at, Some(2.0));
+    }
+
+    #[test]
+    fn compressed_flag_does_not_change_framing() {
+        let (tap, mut framer) = tapped_framer(true);
+        let mut bytes = frame(true, &[0x1f, 0x8b, 0x08]);
+        bytes.extend(frame(false, b"plain"));
+
+        read(&tap, &mut framer, 1, &bytes);
+        assert_eq!(
+            tap.take().unwrap().payload.as_deref(),
+            Some(&[0x1f, 0x8b, 0x08][..])
+        );
+        assert_eq!(tap.take().unwrap().payload.as_deref(), Some(&b"plain"[..]));
+    }
+
+    #[test]
+    fn length_prefix_is_big_endian_and_may_be_zero() {
+        let (tap, mut framer) = tapped_framer(true);
+        // An empty message completes as soon as its header does.
+        read(&tap, &mut framer, 1, &frame(false, &[]));
+        assert_eq!(tap.take().unwrap().payload.as_deref(), Some(&[][..]));
+
+        let payload = vec![7u8; 300];
+        let bytes = frame(false, &payload);
+        assert_eq!(&bytes[1..5], &[0, 0, 1, 44]);
+        read(&tap, &mut framer, 2, &bytes[..299]);
+        assert!(tap.take().is_none());
+        read(&tap, &mut framer, 3, &bytes[299..]);
+        assert_eq!(tap.take().unwrap().payload.unwrap().len(), 300);
+    }
+
+    #[test]
+    fn huge_length_prefix_reserves_a_bounded_buffer() {
+        let (tap, mut framer) = tapped_framer(true);
+        let mut bytes = vec![0];
+        bytes.extend_from_slice(&u32::MAX.to_be_bytes());
+        bytes.extend_from_slice(b"partial");
+
+        read(&tap, &mut framer, 1, &bytes);
+        assert!(tap.take().is_none());
+        assert!(framer.payload.capacity() <= 2 * MAX_PAYLOAD_RESERVE);
+        assert_eq!(framer.remaining, u32::MAX as usize - b"partial".len());
+    }
+
+    #[test]
+    fn payloads_are_dropped_unless_recording() {
+        let (tap, mut framer) = tapped_framer(false);
+        read(&tap, &mut framer, 1, &frame(false, b"data"));
+        let message = tap.take().unwrap();
+        assert_eq!(message.received_at, Some(1.0));
+        assert!(message.payload.is_none());
+        assert!(framer.payload.is_empty());
+    }
+}
@@ -1,25 +1,20 @@
-use std::{collections::HashMap, error::Error, sync::atomic::Ordering};
+use std::{collections::HashMap, error::Error};
 
 use futures_util::{sink::SinkExt, stream::StreamExt};
 use tokio::task;
 use tonic::transport::ClientTlsConfig;
-use tracing::{Level, error, info, warn};
+use tracing::{error, info, warn};
 
 use crate::proto::geyser::{
     CommitmentLevel, SubscribeRequest, SubscribeRequestFilterAccounts,
     SubscribeRequestFilterTransactions, SubscribeRequestPing, subscribe_update::UpdateOneof,
 };
 
-use crate::{
-    config::{Config, Endpoint},
-    utils::{TransactionData, get_current_timestamp, open_log_file, write_log_entry},
-};
+use crate::config::{Config, Endpoint};
 
 use super::{
     GeyserProvider, ProviderContext,
-    common::{
-        TransactionAccumulator, build_signature_envelope, enqueue_signature, fatal_connection_error,
-    },
+    common::{SignatureRecorder, fatal_connection_error, parse_tracked_accounts, tag_transaction},
     yellowstone_client::GeyserGrpcClient,
 };
 
@@ -41,56 +36,11 @@ async fn process_yellowstone_endpoint(
     config: Config,
     context: ProviderContext,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let ProviderContext {
-        shutdown_tx,
-        mut shutdown_rx,
-        start_wallclock_secs,
-        start_instant,
-        comparator,
-        signature_tx,
-        shared_counter,
-        shared_shutdown,
-        target_transactions,
-        total_producers,
-        progress,
-    } = context;
-
-    let signature_sender = signature_tx;
     let endpoint_name = endpoint.name.clone();
-    let mut log_file = if tracing::enabled!(Level::TRACE) {
-        Some(open_log_file(&endpoint_name)?)
-    } else {
-        None
-    };
-
-    let endpoint_url = endpoint.url.clone();
-    let endpoint_token = endpoint
-        .x_token
-        .clone()
-        .filter(|token| !token.trim().is_empty());
-
-    info!(endpoint = %endpoint_name, url = %endpoint_url, "Connecting");
-
-    let builder = GeyserGrpcClient::build_from_shared(endpoint_url.clone())
-        .unwrap_or_else(|err| fatal_connection_error(&endpoint_name, err));
-    let builder = if let Some(token) = endpoint_token {
-        builder
-            .x_token(Some(token))
-            .unwrap_or_else(|err| fatal_connection_error(&endpoint_name, err))
-    } else {
-        builder
-    };
-    let builder = builder
-        .tls_config(ClientTlsConfig::new().with_native_roots())
-        .unwrap_or_else(|err| fatal_connection_error(&endpoint_name, err));
-    let mut client = builder
-        .connect()
-        .await
-        .unwrap_or_else(|err| fatal_connection_error(&endpoint_name, err));
-
-    info!(endpoint = %endpoint_name, "Connected");
+    let (mut recorder, mut shutdown_rx) = SignatureRecorder::new(&endpoint_name, context)?;
 
     let commitment: CommitmentLevel = config.commitment.into();
+    let tracked_accounts = parse_tracked_accounts(&config.accounts)?;
 
     let accounts_filters = HashMap::from([(
         "account".to_string(),
@@ -112,69 +62,58 @@ async fn process_yellowstone_endpoint(
         },
     )]);
 
-    let (mut subscribe_tx, mut stream) = client
-        .subscribe_with_request(Some(SubscribeRequest {
-            slots: HashMap::default(),
-            accounts: accounts_filters,
-            transactions: transactions_filters,
-            transactions_status: HashMap::default(),
-            entry: HashMap::default(),
-            blocks: HashMap::default(),
-            blocks_meta: HashMap::default(),
-            commitment: Some(commitment as i32),
-            accounts_data_slice: Vec::default(),
-            ping: None,
-            from_slot: None,
-        }))
-        .await?;
-
-    let mut accumulator = TransactionAccumulator::new();
-    let mut transaction_count = 0usize;
-
-    let mut record_signature = |signature: String| -> Result<(), Box<dyn Error + Send + Sync>> {
-        let wallclock = get_current_timestamp();
-        let elapsed = start_instant.elapsed();
-
-        if let Some(file) = log_file.as_mut() {
-            write_log_entry(file, wallclock, &endpoint_name, &signature)?;
-        }
-
-        let tx_data = TransactionData {
-            wallclock_secs: wallclock,
-            elapsed_since_start: elapsed,
-            start_wallclock_secs,
-        };
-
-        let updated = accumulator.record(signature.clone(), tx_data.clone());
-
-        if updated
-            && let Some(envelope) = build_signature_envelope(
-                &comparator,
-                &endpoint_name,
-                &signature,
-                tx_data,
-                total_producers,
-            )
-        {
-            if let Some(target) = target_transactions {
-                let shared = shared_counter.fetch_add(1, Ordering::AcqRel) + 1;
-                if let Some(tracker) = progress.as_ref() {
-                    tracker.record(shared);
-                }
-                if shared >= target && !shared_shutdown.swap(true, Ordering::AcqRel) {
-                    info!(endpoint = %endpoint_name, target, "Reached shared signature target; broadcasting shutdown");
-                    let _ = shutdown_tx.send(());
-                }
-            }
+    let request = SubscribeRequest {
+        slots: HashMap::default(),
+        accounts: accounts_filters,
+        transactions: transactions_filters,
+        transactions_status: HashMap::default(),
+        entry: HashMap::default(),
+        blocks: HashMap::default(),
+        blocks_meta: HashMap::default(),
+        commitment: Some(commitment as i32),
+        accounts_data_slice: Vec::default(),
+        ping: None,
+        from_slot: None,
+    };
 
-            if let Some(sender) = signature_sender.as_ref() {
-                enqueue_signature(sender, &endpoint_name, &signature, envelope);
-            }
+    let (mut subscribe_tx, mut stream) = match recorder.replay_stream().await? {
+        Some(stream) => (None, stream),
+        None => {
+            let endpoint_url = endpoint.url.clone();
+            let endpoint_token = endpoint
+                .x_token
+                .clone()
+                .filter(|token| !token.trim().is_empty());
+
+            info!(endpoint = %endpoint_name, url = %endpoint_url, "Connecting");
+
+            let builder = GeyserGrpcClient::build_from_shared(endpoint_url.clone())
+                .unwrap_or_else(|err| fatal_connection_error(&endpoint_name, err));
+            let builder = if let Some(token) = endpoint_token {
+                builder
+                    .x_token(Some(token))
+                    .unwrap_or_else(|err| fatal_connection_error(&endpoint_name, err))
+            } else {
+                builder
+            };
+            let builder = builder
+                .tls_config(ClientTlsConfig::new().with_native_roots())
+                .unwrap_or_else(|err| fatal_connection_error(&endpoint_name, err))
+                .frame_tap(recorder.frame_tap());
+            let mut client = builder
+                .connect()
+                .await
+                .unwrap_or_else(|err| fatal_connection_error(&endpoint_name, err));
+
+            info!(endpoint = %endpoint_name, "Connected");
+            recorder.stream_connected();
+
+            let (subscribe_tx, stream) = client.subscribe_with_request(Some(request)).await?;
+            (Some(subscribe_tx), stream.boxed())
         }
-
-        Ok(())
     };
 
+    recorder.stream_opened();
     loop {
         tokio::select! { biased;
             _ = shutdown_rx.recv() => {
@@ -185,6 +124,8 @@ async fn process_yellowstone_endpoint(
             message = stream.next() => {
                 match message {
                     Some(Ok(msg)) => {
+                        recorder.begin_frame();
+                        recorder.mark_decoded();
                         match msg.update_oneof {
                             Some(UpdateOneof::Account(account_update)) => {
                                 let Some(info) = account_update.account.as_ref() else { continue };
@@ -193,8 +134,10 @@ async fn process_yellowstone_endpoint(
                                     continue;
                                 };
 
-                                record_signature(bs58::encode(signature_bytes).into_string())?;
-                                transaction_count += 1;
+                                let tags = tag_transaction(&tracked_accounts, &[&info.pubkey], std::iter::empty())
+                                    .unwrap_or_default();
+                                recorder.mark_filtered();
+                                recorder.record(bs58::encode(signature_bytes).into_string(), Some(account_update.slot), tags)?;
                             },
                             Some(UpdateOneof::Transaction(tx_msg)) => {
                                 let Some(tx) = tx_msg.transaction.as_ref() else { continue };
@@ -204,10 +147,29 @@ async fn process_yellowstone_endpoint(
                                     continue;
                                 };
 
-                                record_signature(bs58::encode(signature_bytes).into_string())?;
-                                transaction_count += 1;
+                                // The server already filtered on the tracked accounts, which
+                                // may also arrive through address lookup tables.
+                                let mut tags = tx.transaction.as_ref()
+                                    .and_then(|t| t.message.as_ref())
+                                    .and_then(|message| {
+                                        let loaded = tx.meta.iter().flat_map(|meta| {
+                                            meta.loaded_writable_addresses.iter()
+                                                .chain(&meta.loaded_readonly_addresses)
+                                        });
+                                        let keys: Vec<&Vec<u8>> = message.account_keys.iter().chain(loaded).collect();
+                                        tag_transaction(
+                                            &tracked_accounts,
+                                            &keys,
+                                            message.instructions.iter().map(|ix| (ix.program_id_index, ix.data.as_slice())),
+                                        )
+                                    })
+                                    .unwrap_or_default();
+                                tags.succeeded = tx.meta.as_ref().map(|meta| meta.err.is_none());
+                                recorder.mark_filtered();
+                                recorder.record(bs58::encode(signature_bytes).into_string(), Some(tx_msg.slot), tags)?;
                             },
                             Some(UpdateOneof::Ping(_)) => {
+                                let Some(subscribe_tx) = subscribe_tx.as_mut() else { continue };
                                 subscribe_tx
                                     .send(SubscribeRequest {
                                         ping: Some(SubscribeRequestPing { id: 1 }),
@@ -219,6 +181,7 @@ async fn process_yellowstone_endpoint(
                         }
                     },
                     Some(Err(e)) => {
+                        recorder.stream_error();
                         error!(endpoint = %endpoint_name, error = ?e, "Error receiving message from stream");
                         break;
                     },
@@ -231,14 +194,6 @@ async fn process_yellowstone_endpoint(
         }
     }
 
-    let unique_signatures = accumulator.len();
-    let collected = accumulator.into_inner();
-    comparator.add_batch(&endpoint_name, collected);
-    info!(
-        endpoint = %endpoint_name,
-        total_transactions = transaction_count,
-        unique_signatures,
-        "Stream closed after dispatching transactions"
-    );
+    recorder.finish();
     Ok(())
 }
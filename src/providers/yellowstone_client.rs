@@ -5,18 +5,20 @@ use {
         sink::{Sink, SinkExt},
         stream::Stream,
     },
-    std::convert::TryInto,
+    std::{convert::TryInto, sync::Arc},
     tonic::{
         Request, Response, Status,
         codec::Streaming,
         metadata::{AsciiMetadataValue, errors::InvalidMetadataValue},
         service::interceptor::InterceptedService,
-        transport::{ClientTlsConfig, Endpoint, channel::Channel},
+        transport::{ClientTlsConfig, Endpoint},
     },
 };
 
 use crate::proto::geyser::{SubscribeRequest, SubscribeUpdate, geyser_client::GeyserClient};
 
+use super::transport::{FrameTap, TimedChannel, connect_endpoint};
+
 #[derive(Clone, Debug)]
 pub struct InterceptorXToken {
     pub x_token: Option<AsciiMetadataValue>,
@@ -42,7 +44,7 @@ pub enum GeyserGrpcClientError {
 pub type GeyserGrpcClientResult<T> = Result<T, GeyserGrpcClientError>;
 
 pub struct GeyserGrpcClient {
-    geyser: GeyserClient<InterceptedService<Channel, InterceptorXToken>>,
+    geyser: GeyserClient<InterceptedService<TimedChannel, InterceptorXToken>>,
 }
 
 impl GeyserGrpcClient {
@@ -52,21 +54,12 @@ impl GeyserGrpcClient {
         Ok(GeyserGrpcBuilder::new(Endpoint::from_shared(endpoint)?))
     }
 
-    pub async fn subscribe(
-        &mut self,
-    ) -> GeyserGrpcClientResult<(
-        impl Sink<SubscribeRequest, Error = mpsc::SendError>,
-        impl Stream<Item = Result<SubscribeUpdate, Status>>,
-    )> {
-        self.subscribe_with_request(None).await
-    }
-
     pub async fn subscribe_with_request(
         &mut self,
         request: Option<SubscribeRequest>,
     ) -> GeyserGrpcClientResult<(
-        impl Sink<SubscribeRequest, Error = mpsc::SendError>,
-        impl Stream<Item = Result<SubscribeUpdate, Status>>,
+        impl Sink<SubscribeRequest, Error = mpsc::SendError> + use<>,
+        impl Stream<Item = Result<SubscribeUpdate, Status>> + use<>,
     )> {
         let (mut subscribe_tx, subscribe_rx) = mpsc::unbounded();
         if let Some(request) = request {
@@ -80,7 +73,7 @@ impl GeyserGrpcClient {
         Ok((subscribe_tx, response.into_inner()))
     }
 
-    fn new(geyser: GeyserClient<InterceptedService<Channel, InterceptorXToken>>) -> Self {
+    fn new(geyser: GeyserClient<InterceptedService<TimedChannel, InterceptorXToken>>) -> Self {
         Self { geyser }
     }
 }
@@ -98,6 +91,7 @@ pub type GeyserGrpcBuilderResult<T> = Result<T, GeyserGrpcBuilderError>;
 pub struct GeyserGrpcBuilder {
     endpoint: Endpoint,
     x_token: Option<AsciiMetadataValue>,
+    frame_tap: Option<Arc<FrameTap>>,
 }
 
 impl GeyserGrpcBuilder {
@@ -105,15 +99,16 @@ impl GeyserGrpcBuilder {
         Self {
             endpoint,
             x_token: None,
+            frame_tap: None,
         }
     }
 
     pub async fn connect(self) -> GeyserGrpcBuilderResult<GeyserGrpcClient> {
-        let channel = self.endpoint.connect().await?;
+        let channel = connect_endpoint(self.endpoint.clone(), self.frame_tap.clone()).await?;
         self.build(channel)
     }
 
-    fn build(self, channel: Channel) -> GeyserGrpcBuilderResult<GeyserGrpcClient> {
+    fn build(self, channel: TimedChannel) -> GeyserGrpcBuilderResult<GeyserGrpcClient> {
         let interceptor = InterceptorXToken {
             x_token: self.x_token,
         };
@@ -129,6 +124,11 @@ impl GeyserGrpcBuilder {
         Ok(self)
     }
 
+    pub fn frame_tap(mut self, frame_tap: Option<Arc<FrameTap>>) -> Self {
+        self.frame_tap = frame_tap;
+        self
+    }
+
     pub fn tls_config(mut self, tls_config: ClientTlsConfig) -> GeyserGrpcBuilderResult<Self> {
         self.endpoint = self.endpoint.tls_config(tls_config)?;
         Ok(self)
@@ -0,0 +1,387 @@
+use std::{fmt::Write as _, path::Path, str::FromStr};
+
+use anyhow::{Context, Result, anyhow, bail};
+use tracing::info;
+
+use crate::{
+    analysis::{self, EndpointSummary, RunSummary},
+    utils::percentile,
+};
+
+/// Points kept per CDF curve so large runs don't bloat the HTML file.
+const MAX_CDF_POINTS: usize = 200;
+const CDF_RANGE_QUANTILE: f64 = 0.99;
+const CHART_WIDTH: f64 = 640.0;
+const CHART_HEIGHT: f64 = 240.0;
+const CHART_MARGIN: f64 = 40.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Same document as `--metrics-out`.
+    Json,
+    Csv,
+    Markdown,
+    Html,
+}
+
+impl FromStr for ReportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "json" => Ok(ReportFormat::Json),
+            "csv" => Ok(ReportFormat::Csv),
+            "markdown" | "md" => Ok(ReportFormat::Markdown),
+            "html" | "htm" => Ok(ReportFormat::Html),
+            other => Err(anyhow!(
+                "Unknown report format {}; expected json, csv, markdown or html",
+                other
+            )),
+        }
+    }
+}
+
+impl ReportFormat {
+    /// Picks the explicit format, or infers one from the output extension.
+    /// Returns `None` when no report was requested.
+    pub fn resolve(format: Option<ReportFormat>, out: Option<&Path>) -> Result<Option<Self>> {
+        if format.is_some() {
+            return Ok(format);
+        }
+        let Some(path) = out else {
+            return Ok(None);
+        };
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) => ext.parse().map(Some).with_context(|| {
+                format!(
+                    "Cannot infer report format from {}; pass --report-format",
+                    path.display()
+                )
+            }),
+            None => bail!(
+                "Cannot infer report format from {}; pass --report-format",
+                path.display()
+            ),
+        }
+    }
+}
+
+/// Renders the report and writes it to `out`, or to stdout when no path is given.
+pub fn write_report(summary: &RunSummary, format: ReportFormat, out: Option<&Path>) -> Result<()> {
+    let content = render(summary, format)?;
+    match out {
+        Some(path) => {
+            std::fs::write(path, content)
+                .with_context(|| format!("Failed to write report {}", path.display()))?;
+            info!(path = %path.display(), ?format, "Wrote report");
+        }
+        None => println!("{}", content),
+    }
+    Ok(())
+}
+
+pub fn render(summary: &RunSummary, format: ReportFormat) -> Result<String> {
+    match format {
+        ReportFormat::Json => Ok(serde_json::to_string_pretty(
+            &analysis::build_metrics_report(summary),
+        )?),
+        ReportFormat::Csv => render_csv(summary),
+        ReportFormat::Markdown => Ok(render_markdown(summary)),
+        ReportFormat::Html => Ok(render_html(summary)),
+    }
+}
+
+/// Endpoints in the same order as the terminal table.
+fn sorted_endpoints(summary: &RunSummary) -> Vec<&EndpointSummary> {
+    let mut endpoints: Vec<&EndpointSummary> = summary.endpoints.iter().collect();
+    endpoints.sort_by(|a, b| analysis::compare_latency(a, b));
+    endpoints
+}
+
+fn header(summary: &RunSummary) -> Vec<String> {
+    let mut header = vec![
+        "endpoint".to_string(),
+        "first_pct".to_string(),
+        "coverage_pct".to_string(),
+    ];
+    header.extend(summary.percentiles.iter().map(|quantile| {
+        format!(
+            "{}_ms",
+            analysis::percentile_label(*quantile).to_lowercase()
+        )
+    }));
+    header.extend(
+        [
+            "mean_ms",
+            "stddev_ms",
+            "lead_p50_ms",
+            "lead_p95_ms",
+            "valid_tx",
+            "firsts",
+            "missed",
+            "late",
+            "exclusive",
+            "backfill",
+        ]
+        .map(String::from),
+    );
+    header
+}
+
+fn row(summary: &EndpointSummary) -> Vec<String> {
+    let mut row = vec![
+        summary.name.clone(),
+        format!("{:.2}", summary.first_share * 100.0),
+        format!("{:.2}", summary.coverage * 100.0),
+    ];
+    row.extend(
+        summary
+            .delay_percentiles
+            .iter()
+            .map(|value| value.map(|v| format!("{:.3}", v)).unwrap_or_default()),
+    );
+    row.extend(
+        [
+            summary.mean_delay_ms,
+            summary.stddev_delay_ms,
+            summary.lead_p50_ms,
+            summary.lead_p95_ms,
+        ]
+        .map(|value| value.map(|v| format!("{:.3}", v)).unwrap_or_default()),
+    );
+    row.extend([
+        summary.valid_transactions.to_string(),
+        summary.first_detections.to_string(),
+        summary.missed_signatures.to_string(),
+        summary.late_signatures.to_string(),
+        summary.exclusive_signatures.to_string(),
+        summary.backfill_transactions.to_string(),
+    ]);
+    row
+}
+
+const SETUP_HEADER: [&str; 3] = ["endpoint", "connected_ms", "subscribed_ms"];
+
+fn setup_rows(summary: &RunSummary) -> Vec<[String; 3]> {
+    let format_ms = |offset: Option<std::time::Duration>| {
+        offset
+            .map(|offset| format!("{:.0}", offset.as_secs_f64() * 1_000.0))
+            .unwrap_or_default()
+    };
+    summary
+        .stream_setups
+        .iter()
+        .map(|setup| {
+            [
+                setup.name.clone(),
+                format_ms(setup.connected_at),
+                format_ms(setup.subscribed_at),
+            ]
+        })
+        .collect()
+}
+
+fn render_csv(summary: &RunSummary) -> Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(header(summary))?;
+    for endpoint in sorted_endpoints(summary) {
+        writer.write_record(row(endpoint))?;
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|err| anyhow!(err.to_string()))?;
+    Ok(String::from_utf8(bytes)?)
+}
+
+fn render_markdown(summary: &RunSummary) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "## GeyserBench results\n");
+    let _ = writeln!(
+        out,
+        "{} signatures compared, {} backfilled.",
+        summary.total_signatures, summary.backfill_signatures
+    );
+    if let Some(fastest) = summary.fastest_endpoint.as_deref() {
+        let _ = writeln!(out, "Fastest endpoint: **{}**.", fastest);
+    }
+    out.push('\n');
+
+    let header = header(summary);
+    let _ = writeln!(out, "| {} |", header.join(" | "));
+    let _ = writeln!(out, "|{}", "---|".repeat(header.len()));
+    for endpoint in sorted_endpoints(summary) {
+        let _ = writeln!(out, "| {} |", row(endpoint).join(" | "));
+    }
+
+    let setups = setup_rows(summary);
+    if !setups.is_empty() {
+        let _ = writeln!(out, "\n### Stream setup\n");
+        let _ = writeln!(out, "| {} |", SETUP_HEADER.join(" | "));
+        let _ = writeln!(out, "|{}", "---|".repeat(SETUP_HEADER.len()));
+        for setup in setups {
+            let _ = writeln!(out, "| {} |", setup.join(" | "));
+        }
+    }
+    out
+}
+
+fn render_html(summary: &RunSummary) -> String {
+    let mut out = String::new();
+    out.push_str(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>GeyserBench results</title>\n<style>\n\
+         body { font-family: system-ui, sans-serif; margin: 2rem; color: #222; }\n\
+         table { border-collapse: collapse; margin-bottom: 2rem; }\n\
+         th, td { border: 1px solid #ccc; padding: 0.3rem 0.7rem; text-align: right; }\n\
+         th:first-child, td:first-child { text-align: left; }\n\
+         .chart { display: inline-block; margin: 0 1.5rem 1.5rem 0; }\n\
+         svg text { font-size: 11px; fill: #555; }\n\
+         </style>\n</head>\n<body>\n<h1>GeyserBench results</h1>\n",
+    );
+    let _ = writeln!(
+        out,
+        "<p>{} signatures compared, {} backfilled.{}</p>",
+        summary.total_signatures,
+        summary.backfill_signatures,
+        summary
+            .fastest_endpoint
+            .as_deref()
+            .map(|name| format!(" Fastest endpoint: <strong>{}</strong>.", escape(name)))
+            .unwrap_or_default()
+    );
+
+    out.push_str("<table>\n<tr>");
+    for column in header(summary) {
+        let _ = write!(out, "<th>{}</th>", escape(&column));
+    }
+    out.push_str("</tr>\n");
+    let endpoints = sorted_endpoints(summary);
+    for endpoint in &endpoints {
+        out.push_str("<tr>");
+        for cell in row(endpoint) {
+            let _ = write!(out, "<td>{}</td>", escape(&cell));
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table>\n");
+
+    let setups = setup_rows(summary);
+    if !setups.is_empty() {
+        out.push_str("<h2>Stream setup</h2>\n<table>\n<tr>");
+        for column in SETUP_HEADER {
+            let _ = write!(out, "<th>{}</th>", column);
+        }
+        out.push_str("</tr>\n");
+        for setup in setups {
+            out.push_str("<tr>");
+            for cell in setup {
+                let _ = write!(out, "<td>{}</td>", escape(&cell));
+            }
+            out.push_str("</tr>\n");
+        }
+        out.push_str("</table>\n");
+    }
+
+    out.push_str("<h2>Delay to first detection (CDF)</h2>\n");
+
+    // Clip the shared x axis at the slowest P99 so one outlier doesn't flatten every curve.
+    let max_delay = endpoints
+        .iter()
+        .filter(|endpoint| !endpoint.delays_ms.is_empty())
+        .map(|endpoint| percentile(&endpoint.delays_ms, CDF_RANGE_QUANTILE))
+        .fold(0.0f64, f64::max);
+    for endpoint in &endpoints {
+        let _ = writeln!(
+            out,
+            "<div class=\"chart\"><h3>{}</h3>{}</div>",
+            escape(&endpoint.name),
+            cdf_svg(&endpoint.delays_ms, max_delay)
+        );
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Draws the empirical CDF of sorted `delays_ms`. All charts share `max_delay`
+/// as their x range so they can be compared side by side; later samples are
+/// drawn at the right edge.
+fn cdf_svg(delays_ms: &[f64], max_delay: f64) -> String {
+    let plot_width = CHART_WIDTH - 2.0 * CHART_MARGIN;
+    let plot_height = CHART_HEIGHT - 2.0 * CHART_MARGIN;
+    let x_max = if max_delay > 0.0 { max_delay } else { 1.0 };
+    let x = |value: f64| CHART_MARGIN + value.min(x_max) / x_max * plot_width;
+    let y = |fraction: f64| CHART_HEIGHT - CHART_MARGIN - fraction * plot_height;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">",
+        w = CHART_WIDTH,
+        h = CHART_HEIGHT
+    );
+    let _ = write!(
+        svg,
+        "<rect x=\"{m}\" y=\"{m}\" width=\"{pw}\" height=\"{ph}\" fill=\"none\" stroke=\"#ccc\"/>",
+        m = CHART_MARGIN,
+        pw = plot_width,
+        ph = plot_height
+    );
+    let _ = write!(
+        svg,
+        "<text x=\"{}\" y=\"{}\">0</text><text x=\"{}\" y=\"{}\" text-anchor=\"end\">{:.2} ms</text>",
+        CHART_MARGIN,
+        CHART_HEIGHT - CHART_MARGIN + 14.0,
+        CHART_MARGIN + plot_width,
+        CHART_HEIGHT - CHART_MARGIN + 14.0,
+        x_max
+    );
+    let _ = write!(
+        svg,
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">100%</text><text x=\"{}\" y=\"{}\" text-anchor=\"end\">0%</text>",
+        CHART_MARGIN - 4.0,
+        CHART_MARGIN + 4.0,
+        CHART_MARGIN - 4.0,
+        CHART_HEIGHT - CHART_MARGIN
+    );
+
+    if delays_ms.is_empty() {
+        let _ = write!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">Not enough data</text>",
+            CHART_WIDTH / 2.0,
+            CHART_HEIGHT / 2.0
+        );
+    } else {
+        let step = delays_ms.len().div_ceil(MAX_CDF_POINTS).max(1);
+        let total = delays_ms.len() as f64;
+        let mut points = format!("{:.1},{:.1}", x(0.0), y(0.0));
+        let mut indices: Vec<usize> = (0..delays_ms.len()).step_by(step).collect();
+        if indices.last() != Some(&(delays_ms.len() - 1)) {
+            indices.push(delays_ms.len() - 1);
+        }
+        for index in indices {
+            let value = delays_ms[index];
+            let _ = write!(
+                points,
+                " {:.1},{:.1} {:.1},{:.1}",
+                x(value),
+                y(index as f64 / total),
+                x(value),
+                y((index + 1) as f64 / total)
+            );
+        }
+        let _ = write!(
+            svg,
+            "<polyline fill=\"none\" stroke=\"#2a6fdb\" stroke-width=\"2\" points=\"{}\"/>",
+            points
+        );
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,219 @@
+use std::{
+    error::Error,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use hdrhistogram::Histogram;
+use tokio::{
+    sync::{broadcast, oneshot},
+    task::JoinHandle,
+};
+use tracing::{info, warn};
+
+use crate::{
+    config::{Config, Endpoint},
+    providers::{GeyserProvider, ProviderContext},
+};
+
+const LAG_PROBE_INTERVAL: Duration = Duration::from_millis(10);
+const LAG_HISTOGRAM_MAX_US: u64 = 60_000_000;
+
+pub type ProviderResult = Result<(), Box<dyn Error + Send + Sync>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuntimePlacement {
+    Shared,
+    Dedicated { cpu_core: Option<usize> },
+}
+
+impl RuntimePlacement {
+    pub fn for_endpoint(config: &Config, endpoint: &Endpoint) -> Self {
+        if config.dedicated_runtimes {
+            RuntimePlacement::Dedicated {
+                cpu_core: endpoint.cpu_core,
+            }
+        } else {
+            if endpoint.cpu_core.is_some() {
+                warn!(
+                    endpoint = %endpoint.name,
+                    "cpu_core is ignored unless config.dedicated_runtimes is enabled"
+                );
+            }
+            RuntimePlacement::Shared
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            RuntimePlacement::Shared => "shared".to_string(),
+            RuntimePlacement::Dedicated { cpu_core: None } => "dedicated".to_string(),
+            RuntimePlacement::Dedicated {
+                cpu_core: Some(core),
+            } => format!("dedicated (core {core})"),
+        }
+    }
+}
+
+/// Scheduling delay observed by a periodic timer on the runtime that drives an
+/// endpoint. High values mean the client, not the feed, is the bottleneck.
+#[derive(Debug)]
+pub struct EventLoopLag {
+    histogram: Mutex<Histogram<u64>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LagSnapshot {
+    pub samples: u64,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+impl EventLoopLag {
+    pub fn new() -> Self {
+        let histogram = Histogram::new_with_bounds(1, LAG_HISTOGRAM_MAX_US, 3)
+            .expect("valid lag histogram bounds");
+        Self {
+            histogram: Mutex::new(histogram),
+        }
+    }
+
+    fn record(&self, lag: Duration) {
+        let micros = (lag.as_micros() as u64).clamp(1, LAG_HISTOGRAM_MAX_US);
+        if let Ok(mut histogram) = self.histogram.lock() {
+            histogram.saturating_record(micros);
+        }
+    }
+
+    pub fn snapshot(&self) -> Option<LagSnapshot> {
+        let histogram = self.histogram.lock().ok()?;
+        if histogram.is_empty() {
+            return None;
+        }
+
+        Some(LagSnapshot {
+            samples: histogram.len(),
+            mean_ms: histogram.mean() / 1_000.0,
+            p50_ms: histogram.value_at_quantile(0.5) as f64 / 1_000.0,
+            p99_ms: histogram.value_at_quantile(0.99) as f64 / 1_000.0,
+            max_ms: histogram.max() as f64 / 1_000.0,
+        })
+    }
+}
+
+/// Probes the shared runtime once, however many endpoints run on it.
+pub fn spawn_shared_lag_probe(shutdown_rx: broadcast::Receiver<()>) -> Arc<EventLoopLag> {
+    let lag = Arc::new(EventLoopLag::new());
+    tokio::spawn(run_lag_probe(lag.clone(), shutdown_rx));
+    lag
+}
+
+/// Runs a provider either on the shared runtime or on its own OS thread with a
+/// current-thread runtime. Dedicated runtimes get their own lag probe, which is
+/// returned alongside the task.
+pub fn launch_provider(
+    provider: Box<dyn GeyserProvider>,
+    endpoint: Endpoint,
+    config: Config,
+    context: ProviderContext,
+    placement: RuntimePlacement,
+) -> (JoinHandle<ProviderResult>, Option<Arc<EventLoopLag>>) {
+    match placement {
+        RuntimePlacement::Shared => (provider.process(endpoint, config, context), None),
+        RuntimePlacement::Dedicated { cpu_core } => {
+            let lag = Arc::new(EventLoopLag::new());
+            let probe_lag = lag.clone();
+            let probe_shutdown = context.shutdown_tx.subscribe();
+            let (result_tx, result_rx) = oneshot::channel();
+            let endpoint_name = endpoint.name.clone();
+            let thread_name = format!("gb-{}", endpoint_name);
+
+            let spawn_result = thread::Builder::new().name(thread_name).spawn(move || {
+                if let Some(core) = cpu_core {
+                    match pin_current_thread(core) {
+                        Ok(()) => info!(endpoint = %endpoint.name, core, "Pinned endpoint runtime"),
+                        Err(err) => {
+                            warn!(endpoint = %endpoint.name, core, error = %err, "Failed to pin endpoint runtime")
+                        }
+                    }
+                }
+
+                let runtime = match tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                {
+                    Ok(runtime) => runtime,
+                    Err(err) => {
+                        let _ = result_tx.send(Err(err.into()));
+                        return;
+                    }
+                };
+
+                let result = runtime.block_on(async move {
+                    tokio::spawn(run_lag_probe(probe_lag, probe_shutdown));
+                    match provider.process(endpoint, config, context).await {
+                        Ok(result) => result,
+                        Err(err) => Err(err.into()),
+                    }
+                });
+                let _ = result_tx.send(result);
+            });
+
+            if let Err(err) = spawn_result {
+                return (tokio::spawn(async move { Err(err.into()) }), Some(lag));
+            }
+
+            let handle = tokio::spawn(async move {
+                result_rx.await.unwrap_or_else(|_| {
+                    Err(
+                        format!("runtime thread for {endpoint_name} exited without a result")
+                            .into(),
+                    )
+                })
+            });
+            (handle, Some(lag))
+        }
+    }
+}
+
+async fn run_lag_probe(lag: Arc<EventLoopLag>, mut shutdown_rx: broadcast::Receiver<()>) {
+    loop {
+        let expected = Instant::now() + LAG_PROBE_INTERVAL;
+        tokio::select! {
+            _ = shutdown_rx.recv() => break,
+            _ = tokio::time::sleep_until(expected.into()) => {
+                lag.record(Instant::now().saturating_duration_since(expected));
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn pin_current_thread(core: usize) -> std::io::Result<()> {
+    if core >= libc::CPU_SETSIZE as usize {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("core {core} is outside the CPU set"),
+        ));
+    }
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_ZERO(&mut set);
+        libc::CPU_SET(core, &mut set);
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn pin_current_thread(_core: usize) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "CPU pinning is only supported on Linux",
+    ))
+}
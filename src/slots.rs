@@ -0,0 +1,225 @@
+use std::{collections::BTreeMap, fs::File, io::BufWriter, path::Path};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::{stats::SignatureSample, utils::percentile};
+
+/// Width of each position band, measured from the first signature seen in
+/// the slot. Slots are nominally 400 ms, so the last band is open-ended.
+pub const POSITION_BAND_MS: f64 = 100.0;
+const POSITION_BANDS: usize = 4;
+
+#[derive(Debug, Clone, Default)]
+pub struct SlotAnalysis {
+    pub endpoint_names: Vec<String>,
+    /// Signatures grouped by their consensus slot, in slot order.
+    pub slots: Vec<SlotRow>,
+    /// One per endpoint, in the run's endpoint order.
+    pub endpoints: Vec<EndpointSlotStats>,
+    pub positions: Vec<PositionBand>,
+    /// Signatures at least one endpoint reported a slot for.
+    pub signatures_with_slot: usize,
+    /// Signatures endpoints reported in different slots.
+    pub disagreements: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct SlotRow {
+    pub slot: u64,
+    pub signatures: usize,
+    /// Signatures each endpoint delivered first, in endpoint order.
+    pub firsts: Vec<usize>,
+    /// Endpoint with strictly the most firsts in this slot.
+    pub leader: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EndpointSlotStats {
+    pub slots_led: usize,
+    /// Signatures this endpoint reported a slot for.
+    pub reported: usize,
+    /// Reported slots that differ from the consensus slot.
+    pub mismatches: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct PositionBand {
+    pub start_ms: f64,
+    /// `None` for the open-ended last band.
+    pub end_ms: Option<f64>,
+    pub signatures: usize,
+    pub endpoints: Vec<BandStats>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BandStats {
+    pub firsts: usize,
+    pub p50_delay_ms: Option<f64>,
+}
+
+/// Attributes each compared signature to the slot most endpoints reported
+/// for it, then breaks down wins per slot and by position within the slot.
+pub fn build_slot_analysis(samples: &[SignatureSample], names: &[String]) -> SlotAnalysis {
+    let mut analysis = SlotAnalysis {
+        endpoint_names: names.to_vec(),
+        endpoints: vec![EndpointSlotStats::default(); names.len()],
+        ..Default::default()
+    };
+
+    let mut by_slot: BTreeMap<u64, Vec<&SignatureSample>> = BTreeMap::new();
+    for sample in samples {
+        let Some(slot) = consensus_slot(&sample.slots) else {
+            continue;
+        };
+        analysis.signatures_with_slot += 1;
+        let mut disagreed = false;
+        for (stats, reported) in analysis.endpoints.iter_mut().zip(&sample.slots) {
+            let Some(reported) = reported else {
+                continue;
+            };
+            stats.reported += 1;
+            if *reported != slot {
+                stats.mismatches += 1;
+                disagreed = true;
+            }
+        }
+        if disagreed {
+            analysis.disagreements += 1;
+        }
+        by_slot.entry(slot).or_default().push(sample);
+    }
+
+    let mut band_delays: Vec<Vec<Vec<f64>>> = vec![vec![Vec::new(); names.len()]; POSITION_BANDS];
+    analysis.positions = (0..POSITION_BANDS)
+        .map(|band| PositionBand {
+            start_ms: band as f64 * POSITION_BAND_MS,
+            end_ms: (band + 1 < POSITION_BANDS).then(|| (band + 1) as f64 * POSITION_BAND_MS),
+            signatures: 0,
+            endpoints: vec![BandStats::default(); names.len()],
+        })
+        .collect();
+
+    for (slot, slot_samples) in by_slot {
+        let slot_start = slot_samples
+            .iter()
+            .map(|sample| sample.at_secs)
+            .fold(f64::INFINITY, f64::min);
+        let mut firsts = vec![0usize; names.len()];
+        for sample in &slot_samples {
+            firsts[sample.first] += 1;
+
+            let position_ms = (sample.at_secs - slot_start) * 1_000.0;
+            let band = ((position_ms / POSITION_BAND_MS) as usize).min(POSITION_BANDS - 1);
+            let position = &mut analysis.positions[band];
+            position.signatures += 1;
+            position.endpoints[sample.first].firsts += 1;
+            for (endpoint, delay) in sample.delays_ms.iter().enumerate() {
+                if let Some(delay) = delay {
+                    band_delays[band][endpoint].push(*delay);
+                }
+            }
+        }
+
+        let most = firsts.iter().copied().max().unwrap_or_default();
+        let mut leaders = firsts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count == most);
+        let leader = match (leaders.next(), leaders.next()) {
+            (Some((endpoint, _)), None) if most > 0 => Some(endpoint),
+            _ => None,
+        };
+        if let Some(endpoint) = leader {
+            analysis.endpoints[endpoint].slots_led += 1;
+        }
+        analysis.slots.push(SlotRow {
+            slot,
+            signatures: slot_samples.len(),
+            firsts,
+            leader,
+        });
+    }
+
+    for (position, delays) in analysis.positions.iter_mut().zip(band_delays.iter_mut()) {
+        for (stats, sorted) in position.endpoints.iter_mut().zip(delays.iter_mut()) {
+            if sorted.is_empty() {
+                continue;
+            }
+            sorted.sort_by(f64::total_cmp);
+            stats.p50_delay_ms = Some(percentile(sorted, 0.5));
+        }
+    }
+
+    analysis
+}
+
+/// Most commonly reported slot, preferring the earliest on a tie.
+pub fn consensus_slot(reported: &[Option<u64>]) -> Option<u64> {
+    let mut counts: BTreeMap<u64, usize> = BTreeMap::new();
+    for slot in reported.iter().flatten() {
+        *counts.entry(*slot).or_default() += 1;
+    }
+    let most = counts.values().copied().max()?;
+    counts
+        .into_iter()
+        .find(|(_, count)| *count == most)
+        .map(|(slot, _)| slot)
+}
+
+#[derive(Serialize)]
+struct SlotCsvRow<'a> {
+    slot: u64,
+    endpoint: &'a str,
+    signatures: usize,
+    firsts: usize,
+    win_rate: f64,
+    leader: bool,
+}
+
+/// Writes one CSV row per slot and endpoint.
+pub fn write_slots(analysis: &SlotAnalysis, path: &Path) -> Result<()> {
+    let file =
+        File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut writer = csv::Writer::from_writer(BufWriter::new(file));
+    for row in &analysis.slots {
+        for (endpoint, name) in analysis.endpoint_names.iter().enumerate() {
+            writer.serialize(SlotCsvRow {
+                slot: row.slot,
+                endpoint: name,
+                signatures: row.signatures,
+                firsts: row.firsts[endpoint],
+                win_rate: row.firsts[endpoint] as f64 / row.signatures as f64,
+                leader: row.leader == Some(endpoint),
+            })?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn consensus_slot_takes_the_majority() {
+        assert_eq!(consensus_slot(&[Some(12), Some(11), Some(12)]), Some(12));
+        assert_eq!(
+            consensus_slot(&[None, Some(11), Some(12), Some(12)]),
+            Some(12)
+        );
+    }
+
+    #[test]
+    fn consensus_slot_prefers_the_earliest_on_a_tie() {
+        assert_eq!(consensus_slot(&[Some(12), Some(11)]), Some(11));
+        assert_eq!(consensus_slot(&[Some(13), None, Some(12)]), Some(12));
+    }
+
+    #[test]
+    fn consensus_slot_needs_a_reported_slot() {
+        assert_eq!(consensus_slot(&[]), None);
+        assert_eq!(consensus_slot(&[None, None]), None);
+    }
+}
@@ -0,0 +1,428 @@
+use crate::utils::{SplitMix64, percentile};
+
+/// Coverage of the bootstrap confidence intervals.
+pub const CONFIDENCE_LEVEL: f64 = 0.95;
+/// Two-sided p-value below which a pairwise difference is called significant.
+pub const SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+const MAX_BOOTSTRAP_RESAMPLES: usize = 1_000;
+const MIN_BOOTSTRAP_RESAMPLES: usize = 100;
+/// Caps resamples × signatures so large runs still summarise quickly.
+const BOOTSTRAP_DRAW_BUDGET: usize = 20_000_000;
+/// Fixed so the same observations always give the same intervals.
+const BOOTSTRAP_SEED: u64 = 0x6765_7973_6572_6265;
+
+/// One compared signature: the endpoint that delivered it first and each
+/// endpoint's delay to first, `None` when it was missed or late.
+#[derive(Debug, Clone)]
+pub struct SignatureSample {
+    /// Seconds since run start when the first endpoint delivered it.
+    pub at_secs: f64,
+    pub first: usize,
+    pub delays_ms: Vec<Option<f64>>,
+    /// Slot each endpoint reported, late deliveries included.
+    pub slots: Vec<Option<u64>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    pub low: f64,
+    pub high: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EndpointIntervals {
+    pub first_share: Option<Interval>,
+    /// One per requested percentile, in the same order.
+    pub delay_percentiles: Vec<Option<Interval>>,
+}
+
+/// Paired sign test between two endpoints over the signatures both delivered
+/// on time.
+#[derive(Debug, Clone)]
+pub struct PairwiseTest {
+    pub lhs: String,
+    pub rhs: String,
+    pub lhs_faster: usize,
+    pub rhs_faster: usize,
+    pub ties: usize,
+    pub p_value: f64,
+    /// Median of `lhs` minus `rhs` arrival time; negative when `lhs` tends
+    /// to be earlier.
+    pub median_delta_ms: Option<f64>,
+}
+
+impl PairwiseTest {
+    /// The endpoint that won more signatures, if the difference is
+    /// significant at `alpha`.
+    pub fn winner(&self, alpha: f64) -> Option<&str> {
+        if self.p_value >= alpha || self.lhs_faster == self.rhs_faster {
+            return None;
+        }
+        if self.lhs_faster > self.rhs_faster {
+            Some(&self.lhs)
+        } else {
+            Some(&self.rhs)
+        }
+    }
+
+    pub fn involves(&self, endpoint: &str) -> bool {
+        self.lhs == endpoint || self.rhs == endpoint
+    }
+
+    /// Signatures both endpoints delivered on time.
+    pub fn shared(&self) -> usize {
+        self.lhs_faster + self.rhs_faster + self.ties
+    }
+
+    /// Share of shared signatures `endpoint` delivered strictly first, and
+    /// its median lead from `endpoint`'s side, when it is part of this test.
+    pub fn seen_from(&self, endpoint: &str) -> Option<(f64, Option<f64>)> {
+        let (wins, delta) = if endpoint == self.lhs {
+            (self.lhs_faster, self.median_delta_ms)
+        } else if endpoint == self.rhs {
+            (self.rhs_faster, self.median_delta_ms.map(|delta| -delta))
+        } else {
+            return None;
+        };
+        let shared = self.shared();
+        let win_rate = if shared > 0 {
+            wins as f64 / shared as f64
+        } else {
+            f64::NAN
+        };
+        Some((win_rate, delta))
+    }
+}
+
+/// Percentile-bootstrap intervals for each endpoint's first share and delay
+/// percentiles, resampling whole signatures so endpoints stay paired.
+pub fn bootstrap_intervals(
+    samples: &[SignatureSample],
+    endpoints: usize,
+    percentiles: &[f64],
+) -> Vec<EndpointIntervals> {
+    if samples.is_empty() {
+        return vec![
+            EndpointIntervals {
+                first_share: None,
+                delay_percentiles: vec![None; percentiles.len()],
+            };
+            endpoints
+        ];
+    }
+
+    let resamples = (BOOTSTRAP_DRAW_BUDGET / samples.len())
+        .clamp(MIN_BOOTSTRAP_RESAMPLES, MAX_BOOTSTRAP_RESAMPLES);
+    let mut rng = SplitMix64::new(BOOTSTRAP_SEED);
+    let mut first_shares = vec![Vec::with_capacity(resamples); endpoints];
+    let mut delay_estimates =
+        vec![vec![Vec::with_capacity(resamples); percentiles.len()]; endpoints];
+    let mut firsts = vec![0usize; endpoints];
+    let mut delays: Vec<Vec<f64>> = vec![Vec::with_capacity(samples.len()); endpoints];
+
+    for _ in 0..resamples {
+        firsts.fill(0);
+        delays.iter_mut().for_each(Vec::clear);
+        for _ in 0..samples.len() {
+            let sample = &samples[(rng.next_u64() % samples.len() as u64) as usize];
+            firsts[sample.first] += 1;
+            for (endpoint, delay) in sample.delays_ms.iter().enumerate() {
+                if let Some(delay) = delay {
+                    delays[endpoint].push(*delay);
+                }
+            }
+        }
+
+        for endpoint in 0..endpoints {
+            first_shares[endpoint].push(firsts[endpoint] as f64 / samples.len() as f64);
+            let sorted = &mut delays[endpoint];
+            if sorted.is_empty() {
+                continue;
+            }
+            sorted.sort_by(f64::total_cmp);
+            for (estimates, quantile) in delay_estimates[endpoint].iter_mut().zip(percentiles) {
+                estimates.push(percentile(sorted, *quantile));
+            }
+        }
+    }
+
+    first_shares
+        .into_iter()
+        .zip(delay_estimates)
+        .map(|(shares, delays)| EndpointIntervals {
+            first_share: interval(shares),
+            delay_percentiles: delays.into_iter().map(interval).collect(),
+        })
+        .collect()
+}
+
+fn interval(mut estimates: Vec<f64>) -> Option<Interval> {
+    if estimates.is_empty() {
+        return None;
+    }
+    estimates.sort_by(f64::total_cmp);
+    let tail = (1.0 - CONFIDENCE_LEVEL) / 2.0;
+    Some(Interval {
+        low: percentile(&estimates, tail),
+        high: percentile(&estimates, 1.0 - tail),
+    })
+}
+
+/// Sign test for every pair of endpoints on the per-signature difference in
+/// arrival time.
+pub fn pairwise_sign_tests(samples: &[SignatureSample], names: &[String]) -> Vec<PairwiseTest> {
+    let mut tests = Vec::new();
+    for lhs in 0..names.len() {
+        for rhs in lhs + 1..names.len() {
+            let (mut lhs_faster, mut rhs_faster, mut ties) = (0, 0, 0);
+            let mut deltas = Vec::new();
+            for sample in samples {
+                let (Some(lhs_delay), Some(rhs_delay)) =
+                    (sample.delays_ms[lhs], sample.delays_ms[rhs])
+                else {
+                    continue;
+                };
+                deltas.push(lhs_delay - rhs_delay);
+                match lhs_delay.total_cmp(&rhs_delay) {
+                    std::cmp::Ordering::Less => lhs_faster += 1,
+                    std::cmp::Ordering::Greater => rhs_faster += 1,
+                    std::cmp::Ordering::Equal => ties += 1,
+                }
+            }
+            deltas.sort_by(f64::total_cmp);
+            tests.push(PairwiseTest {
+                lhs: names[lhs].clone(),
+                rhs: names[rhs].clone(),
+                lhs_faster,
+                rhs_faster,
+                ties,
+                p_value: sign_test_p_value(lhs_faster, rhs_faster),
+                median_delta_ms: (!deltas.is_empty()).then(|| percentile(&deltas, 0.5)),
+            });
+        }
+    }
+    tests
+}
+
+/// Exact two-sided sign test p-value; ties are expected to be left out.
+pub fn sign_test_p_value(wins: usize, losses: usize) -> f64 {
+    let trials = wins + losses;
+    if trials == 0 {
+        return 1.0;
+    }
+    // Sums P(X <= k) for X ~ Binomial(trials, 0.5) in log space so large
+    // runs do not overflow the binomial coefficients.
+    let ln_half_pow = trials as f64 * 0.5f64.ln();
+    let mut ln_choose = 0.0;
+    let mut tail = 0.0;
+    for k in 0..=wins.min(losses) {
+        if k > 0 {
+            ln_choose += ((trials - k + 1) as f64).ln() - (k as f64).ln();
+        }
+        tail += (ln_choose + ln_half_pow).exp();
+    }
+    (2.0 * tail).min(1.0)
+}
+
+/// Pearson chi-square test of independence on a contingency table.
+#[derive(Debug, Clone, Copy)]
+pub struct ChiSquareTest {
+    pub statistic: f64,
+    pub degrees_of_freedom: usize,
+    pub p_value: f64,
+}
+
+/// Tests whether rows and columns of `table` are independent. Rows and
+/// columns that sum to zero are dropped; `None` when fewer than two of either
+/// remain.
+pub fn chi_square_independence(table: &[Vec<usize>]) -> Option<ChiSquareTest> {
+    let columns = table.first().map_or(0, Vec::len);
+    let column_totals: Vec<usize> = (0..columns)
+        .map(|column| table.iter().map(|row| row[column]).sum())
+        .collect();
+    let kept_columns: Vec<usize> = (0..columns)
+        .filter(|column| column_totals[*column] > 0)
+        .collect();
+    let rows: Vec<&Vec<usize>> = table
+        .iter()
+        .filter(|row| row.iter().sum::<usize>() > 0)
+        .collect();
+    if rows.len() < 2 || kept_columns.len() < 2 {
+        return None;
+    }
+
+    let total: usize = kept_columns
+        .iter()
+        .map(|column| column_totals[*column])
+        .sum();
+    let mut statistic = 0.0;
+    for row in &rows {
+        let row_total: usize = kept_columns.iter().map(|column| row[*column]).sum();
+        for column in &kept_columns {
+            let expected = row_total as f64 * column_totals[*column] as f64 / total as f64;
+            if expected > 0.0 {
+                let diff = row[*column] as f64 - expected;
+                statistic += diff * diff / expected;
+            }
+        }
+    }
+
+    let degrees_of_freedom = (rows.len() - 1) * (kept_columns.len() - 1);
+    Some(ChiSquareTest {
+        statistic,
+        degrees_of_freedom,
+        p_value: upper_regularized_gamma(degrees_of_freedom as f64 / 2.0, statistic / 2.0),
+    })
+}
+
+const GAMMA_EPSILON: f64 = 1e-12;
+const GAMMA_MAX_ITERATIONS: usize = 500;
+
+/// Q(a, x), the chi-square survival function for `a = dof / 2` and
+/// `x = statistic / 2`.
+fn upper_regularized_gamma(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let prefactor = (-x + a * x.ln() - ln_gamma(a)).exp();
+    if x < a + 1.0 {
+        // Series for the lower function converges quickly here.
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        let mut n = a;
+        for _ in 0..GAMMA_MAX_ITERATIONS {
+            n += 1.0;
+            term *= x / n;
+            sum += term;
+            if term.abs() < sum.abs() * GAMMA_EPSILON {
+                break;
+            }
+        }
+        (1.0 - sum * prefactor).clamp(0.0, 1.0)
+    } else {
+        // Modified Lentz continued fraction for the upper function.
+        let tiny = f64::MIN_POSITIVE / GAMMA_EPSILON;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut fraction = d;
+        for i in 1..GAMMA_MAX_ITERATIONS {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            fraction *= delta;
+            if (delta - 1.0).abs() < GAMMA_EPSILON {
+                break;
+            }
+        }
+        (fraction * prefactor).clamp(0.0, 1.0)
+    }
+}
+
+/// Lanczos approximation, accurate to about 15 digits for `x > 0`.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    let x = x - 1.0;
+    let mut sum = COEFFICIENTS[0];
+    for (index, coefficient) in COEFFICIENTS.iter().enumerate().skip(1) {
+        sum += coefficient / (x + index as f64);
+    }
+    let t = x + 7.5;
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() < tolerance,
+            "{actual} is not within {tolerance} of {expected}"
+        );
+    }
+
+    #[test]
+    fn sign_test_matches_exact_binomial() {
+        // 2 * (C(10,0) + C(10,1)) / 2^10
+        assert_close(sign_test_p_value(9, 1), 22.0 / 1024.0, 1e-12);
+        assert_close(sign_test_p_value(1, 9), 22.0 / 1024.0, 1e-12);
+        assert_eq!(sign_test_p_value(5, 5), 1.0);
+        assert_eq!(sign_test_p_value(0, 0), 1.0);
+    }
+
+    #[test]
+    fn chi_square_survival_matches_tables() {
+        // Continued-fraction branch: the 5% critical value for 1 df.
+        assert_close(upper_regularized_gamma(0.5, 3.84 / 2.0), 0.05, 1e-3);
+        // Series branch.
+        assert_close(upper_regularized_gamma(0.5, 1.0 / 2.0), 0.3173, 1e-4);
+        assert_close(upper_regularized_gamma(1.0, 2.0), (-2.0f64).exp(), 1e-10);
+    }
+
+    #[test]
+    fn ln_gamma_matches_factorials() {
+        assert_close(ln_gamma(5.0), 24.0f64.ln(), 1e-12);
+        assert_close(ln_gamma(1.0), 0.0, 1e-12);
+        assert_close(ln_gamma(0.5), std::f64::consts::PI.sqrt().ln(), 1e-12);
+    }
+
+    #[test]
+    fn bootstrap_intervals_contain_point_estimates() {
+        let mut rng = SplitMix64::new(7);
+        let samples: Vec<SignatureSample> = (0..200)
+            .map(|index| {
+                let lag = (rng.next_u64() % 1_000) as f64 / 100.0;
+                let first = usize::from(index % 4 == 0);
+                let mut delays_ms = vec![Some(lag); 2];
+                delays_ms[first] = Some(0.0);
+                SignatureSample {
+                    at_secs: index as f64,
+                    first,
+                    delays_ms,
+                    slots: vec![None; 2],
+                }
+            })
+            .collect();
+
+        let intervals = bootstrap_intervals(&samples, 2, &[0.5]);
+        for (endpoint, intervals) in intervals.iter().enumerate() {
+            let firsts = samples
+                .iter()
+                .filter(|sample| sample.first == endpoint)
+                .count();
+            let share = firsts as f64 / samples.len() as f64;
+            let mut delays: Vec<f64> = samples
+                .iter()
+                .filter_map(|sample| sample.delays_ms[endpoint])
+                .collect();
+            delays.sort_by(f64::total_cmp);
+            let median = percentile(&delays, 0.5);
+
+            let first_share = intervals.first_share.unwrap();
+            assert!(first_share.low <= share && share <= first_share.high);
+            let delay = intervals.delay_percentiles[0].unwrap();
+            assert!(delay.low <= median && median <= delay.high);
+        }
+    }
+}
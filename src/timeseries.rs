@@ -0,0 +1,286 @@
+use std::{fs::File, io::BufWriter, path::Path};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::{stats::SignatureSample, utils::percentile};
+
+pub const DEFAULT_BUCKET_SECS: f64 = 10.0;
+
+/// A bucket is degraded when its P95 exceeds both this multiple of the
+/// endpoint's baseline P95 and the baseline plus `INCIDENT_MIN_EXCESS_MS`.
+const INCIDENT_P95_FACTOR: f64 = 3.0;
+const INCIDENT_MIN_EXCESS_MS: f64 = 1.0;
+/// Buckets with fewer deliveries are too noisy to judge.
+const MIN_BUCKET_SAMPLES: usize = 5;
+/// Fewer judged buckets than this leave no meaningful baseline.
+const MIN_BASELINE_BUCKETS: usize = 3;
+
+#[derive(Debug, Clone, Default)]
+pub struct Timeline {
+    pub bucket_secs: f64,
+    pub endpoint_names: Vec<String>,
+    pub buckets: Vec<Bucket>,
+    pub incidents: Vec<Incident>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Bucket {
+    /// Seconds since run start.
+    pub start_secs: f64,
+    pub signatures: usize,
+    /// One per endpoint, in the run's endpoint order.
+    pub endpoints: Vec<BucketStats>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BucketStats {
+    pub delivered: usize,
+    pub firsts: usize,
+    pub p50_delay_ms: Option<f64>,
+    pub p95_delay_ms: Option<f64>,
+    pub degraded: bool,
+}
+
+/// Consecutive buckets in which one endpoint's P95 rose well above its own
+/// baseline.
+#[derive(Debug, Clone)]
+pub struct Incident {
+    pub endpoint: String,
+    pub start_secs: f64,
+    pub end_secs: f64,
+    pub baseline_p95_ms: f64,
+    pub peak_p95_ms: f64,
+}
+
+/// Buckets compared signatures by when they were first delivered and flags
+/// degraded buckets per endpoint.
+pub fn build_timeline(samples: &[SignatureSample], names: &[String], bucket_secs: f64) -> Timeline {
+    let bucket_secs = if bucket_secs > 0.0 {
+        bucket_secs
+    } else {
+        DEFAULT_BUCKET_SECS
+    };
+    let bucket_of = |sample: &SignatureSample| (sample.at_secs / bucket_secs).floor() as i64;
+    let (Some(first), Some(last)) = (
+        samples.iter().map(bucket_of).min(),
+        samples.iter().map(bucket_of).max(),
+    ) else {
+        return Timeline {
+            bucket_secs,
+            endpoint_names: names.to_vec(),
+            ..Default::default()
+        };
+    };
+
+    let count = (last - first + 1) as usize;
+    let mut delays: Vec<Vec<Vec<f64>>> = vec![vec![Vec::new(); names.len()]; count];
+    let mut buckets: Vec<Bucket> = (0..count)
+        .map(|offset| Bucket {
+            start_secs: (first + offset as i64) as f64 * bucket_secs,
+            signatures: 0,
+            endpoints: vec![BucketStats::default(); names.len()],
+        })
+        .collect();
+
+    for sample in samples {
+        let index = (bucket_of(sample) - first) as usize;
+        let bucket = &mut buckets[index];
+        bucket.signatures += 1;
+        bucket.endpoints[sample.first].firsts += 1;
+        for (endpoint, delay) in sample.delays_ms.iter().enumerate() {
+            if let Some(delay) = delay {
+                bucket.endpoints[endpoint].delivered += 1;
+                delays[index][endpoint].push(*delay);
+            }
+        }
+    }
+
+    for (bucket, bucket_delays) in buckets.iter_mut().zip(delays.iter_mut()) {
+        for (stats, sorted) in bucket.endpoints.iter_mut().zip(bucket_delays.iter_mut()) {
+            if sorted.is_empty() {
+                continue;
+            }
+            sorted.sort_by(f64::total_cmp);
+            stats.p50_delay_ms = Some(percentile(sorted, 0.5));
+            stats.p95_delay_ms = Some(percentile(sorted, 0.95));
+        }
+    }
+
+    let incidents = names
+        .iter()
+        .enumerate()
+        .flat_map(|(endpoint, name)| detect_incidents(&mut buckets, endpoint, name, bucket_secs))
+        .collect();
+
+    Timeline {
+        bucket_secs,
+        endpoint_names: names.to_vec(),
+        buckets,
+        incidents,
+    }
+}
+
+fn detect_incidents(
+    buckets: &mut [Bucket],
+    endpoint: usize,
+    name: &str,
+    bucket_secs: f64,
+) -> Vec<Incident> {
+    let judged = |bucket: &Bucket| {
+        let stats = &bucket.endpoints[endpoint];
+        stats
+            .p95_delay_ms
+            .filter(|_| stats.delivered >= MIN_BUCKET_SAMPLES)
+    };
+
+    let mut p95s: Vec<f64> = buckets.iter().filter_map(judged).collect();
+    if p95s.len() < MIN_BASELINE_BUCKETS {
+        return Vec::new();
+    }
+    p95s.sort_by(f64::total_cmp);
+    let baseline = percentile(&p95s, 0.5);
+    let threshold = (baseline * INCIDENT_P95_FACTOR).max(baseline + INCIDENT_MIN_EXCESS_MS);
+
+    let mut incidents: Vec<Incident> = Vec::new();
+    let mut open = false;
+    for bucket in buckets.iter_mut() {
+        let Some(p95) = judged(bucket).filter(|p95| *p95 > threshold) else {
+            open = false;
+            continue;
+        };
+        bucket.endpoints[endpoint].degraded = true;
+        let end_secs = bucket.start_secs + bucket_secs;
+        match incidents.last_mut().filter(|_| open) {
+            Some(incident) => {
+                incident.end_secs = end_secs;
+                incident.peak_p95_ms = incident.peak_p95_ms.max(p95);
+            }
+            None => incidents.push(Incident {
+                endpoint: name.to_string(),
+                start_secs: bucket.start_secs,
+                end_secs,
+                baseline_p95_ms: baseline,
+                peak_p95_ms: p95,
+            }),
+        }
+        open = true;
+    }
+    incidents
+}
+
+#[derive(Serialize)]
+struct TimelineRow<'a> {
+    bucket_start_secs: f64,
+    endpoint: &'a str,
+    signatures: usize,
+    delivered: usize,
+    firsts: usize,
+    win_rate: Option<f64>,
+    p50_ms: Option<f64>,
+    p95_ms: Option<f64>,
+    degraded: bool,
+}
+
+/// Writes one CSV row per bucket and endpoint, ready for plotting.
+pub fn write_timeline(timeline: &Timeline, path: &Path) -> Result<()> {
+    let file =
+        File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut writer = csv::Writer::from_writer(BufWriter::new(file));
+    for bucket in &timeline.buckets {
+        for (name, stats) in timeline.endpoint_names.iter().zip(&bucket.endpoints) {
+            writer.serialize(TimelineRow {
+                bucket_start_secs: bucket.start_secs,
+                endpoint: name,
+                signatures: bucket.signatures,
+                delivered: stats.delivered,
+                firsts: stats.firsts,
+                win_rate: (bucket.signatures > 0)
+                    .then(|| stats.firsts as f64 / bucket.signatures as f64),
+                p50_ms: stats.p50_delay_ms,
+                p95_ms: stats.p95_delay_ms,
+                degraded: stats.degraded,
+            })?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUCKET_SECS: f64 = 10.0;
+
+    /// One single-endpoint bucket per `(delivered, p95)` pair.
+    fn buckets(series: &[(usize, f64)]) -> Vec<Bucket> {
+        series
+            .iter()
+            .enumerate()
+            .map(|(index, (delivered, p95))| Bucket {
+                start_secs: index as f64 * BUCKET_SECS,
+                signatures: *delivered,
+                endpoints: vec![BucketStats {
+                    delivered: *delivered,
+                    p95_delay_ms: Some(*p95),
+                    ..Default::default()
+                }],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn consecutive_degraded_buckets_form_incidents() {
+        let mut buckets = buckets(&[
+            (10, 2.0),
+            (10, 2.0),
+            (10, 2.0),
+            (10, 10.0),
+            (10, 12.0),
+            (10, 2.0),
+            (10, 7.0),
+        ]);
+        let incidents = detect_incidents(&mut buckets, 0, "a", BUCKET_SECS);
+
+        // Baseline is the median P95 (2 ms), so the threshold is 6 ms.
+        assert_eq!(incidents.len(), 2);
+        assert_eq!(
+            (incidents[0].start_secs, incidents[0].end_secs),
+            (30.0, 50.0)
+        );
+        assert_eq!(incidents[0].baseline_p95_ms, 2.0);
+        assert_eq!(incidents[0].peak_p95_ms, 12.0);
+        assert_eq!(
+            (incidents[1].start_secs, incidents[1].end_secs),
+            (60.0, 70.0)
+        );
+        let degraded: Vec<bool> = buckets
+            .iter()
+            .map(|bucket| bucket.endpoints[0].degraded)
+            .collect();
+        assert_eq!(degraded, [false, false, false, true, true, false, true]);
+    }
+
+    #[test]
+    fn sparse_buckets_are_not_judged() {
+        let mut buckets = buckets(&[(10, 2.0), (10, 2.0), (4, 50.0), (10, 2.0)]);
+        assert!(detect_incidents(&mut buckets, 0, "a", BUCKET_SECS).is_empty());
+        assert!(!buckets[2].endpoints[0].degraded);
+    }
+
+    #[test]
+    fn too_few_buckets_leave_no_baseline() {
+        let mut buckets = buckets(&[(10, 2.0), (10, 50.0), (4, 2.0)]);
+        assert!(detect_incidents(&mut buckets, 0, "a", BUCKET_SECS).is_empty());
+    }
+
+    #[test]
+    fn small_baselines_need_the_minimum_excess() {
+        // Three times a 0.2 ms baseline is 0.6 ms, but the threshold is 1.2 ms.
+        let mut buckets = buckets(&[(10, 0.2), (10, 0.2), (10, 0.2), (10, 1.0), (10, 1.5)]);
+        let incidents = detect_incidents(&mut buckets, 0, "a", BUCKET_SECS);
+        assert_eq!(incidents.len(), 1);
+        assert_eq!(incidents[0].start_secs, 40.0);
+    }
+}
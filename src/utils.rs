@@ -1,11 +1,15 @@
 use dashmap::{DashMap, DashSet};
 use std::{
     collections::HashMap,
-    fs::OpenOptions,
+    fs::{File, OpenOptions},
     io::Write,
-    sync::atomic::{AtomicUsize, Ordering},
-    time::{Duration, SystemTime, UNIX_EPOCH},
+    sync::{
+        Mutex, OnceLock,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
+use tokio::sync::Notify;
 use tracing::{info, warn};
 
 #[derive(Debug, Clone)]
@@ -13,12 +17,102 @@ pub struct TransactionData {
     pub wallclock_secs: f64,
     pub elapsed_since_start: Duration,
     pub start_wallclock_secs: f64,
+    /// Socket receive time of the frame carrying this transaction, when
+    /// transport timestamps are enabled.
+    pub receive_wallclock_secs: Option<f64>,
+    pub slot: Option<u64>,
+    pub tags: TransactionTags,
+}
+
+/// What a transaction touched, as base58 keys.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransactionTags {
+    /// Tracked accounts the transaction referenced.
+    pub accounts: Vec<String>,
+    /// Programs its instructions invoked; empty when the feed does not carry
+    /// instructions.
+    pub programs: Vec<String>,
+    /// Whether it executed successfully, for feeds that carry status.
+    pub succeeded: Option<bool>,
+    /// Compute-unit price in micro-lamports, when it sets one.
+    pub cu_price: Option<u64>,
+    /// Whether it references a Jito tip account.
+    pub jito_tip: bool,
+}
+
+impl TransactionTags {
+    /// Adds what `other` knows that these tags do not. Returns whether
+    /// anything changed.
+    pub fn merge(&mut self, other: &TransactionTags) -> bool {
+        let mut changed = false;
+        for (merged, extra) in [
+            (&mut self.accounts, &other.accounts),
+            (&mut self.programs, &other.programs),
+        ] {
+            for key in extra {
+                if !merged.contains(key) {
+                    merged.push(key.clone());
+                    changed = true;
+                }
+            }
+        }
+        if self.succeeded.is_none() && other.succeeded.is_some() {
+            self.succeeded = other.succeeded;
+            changed = true;
+        }
+        if self.cu_price.is_none() && other.cu_price.is_some() {
+            self.cu_price = other.cu_price;
+            changed = true;
+        }
+        if !self.jito_tip && other.jito_tip {
+            self.jito_tip = true;
+            changed = true;
+        }
+        changed
+    }
+}
+
+impl TransactionData {
+    /// Folds in another update for the same signature from the same
+    /// endpoint, such as a transaction following an account update. Keeps the
+    /// earliest arrival and combines both updates' tags. Returns whether
+    /// anything changed.
+    pub fn merge(&mut self, other: TransactionData) -> bool {
+        if other.arrival_elapsed() < self.arrival_elapsed() {
+            let tags = std::mem::take(&mut self.tags);
+            let slot = self.slot;
+            *self = other;
+            self.tags.merge(&tags);
+            self.slot = self.slot.or(slot);
+            true
+        } else {
+            let filled_slot = self.slot.is_none() && other.slot.is_some();
+            self.slot = self.slot.or(other.slot);
+            self.tags.merge(&other.tags) || filled_slot
+        }
+    }
+
+    /// Elapsed time at which the transaction reached the client. Uses the
+    /// transport receive timestamp when present so decode cost is excluded.
+    pub fn arrival_elapsed(&self) -> Duration {
+        match self.receive_wallclock_secs {
+            Some(received) => {
+                let decode_lag = (self.wallclock_secs - received).max(0.0);
+                self.elapsed_since_start
+                    .saturating_sub(Duration::from_secs_f64(decode_lag))
+            }
+            None => self.elapsed_since_start,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct Comparator {
     data: DashMap<String, HashMap<String, TransactionData>>,
     emitted: DashSet<String>,
+    /// Signatures evicted by `drain_before`, keyed to the cutoff that evicted
+    /// them, so late arrivals do not start a new one-endpoint entry.
+    tombstones: DashMap<String, Duration>,
 }
 
 impl Comparator {
@@ -26,6 +120,7 @@ impl Comparator {
         Self {
             data: DashMap::new(),
             emitted: DashSet::new(),
+            tombstones: DashMap::new(),
         }
     }
 
@@ -43,7 +138,7 @@ impl Comparator {
         data: TransactionData,
         expected_producers: usize,
     ) -> Option<HashMap<String, TransactionData>> {
-        if expected_producers == 0 {
+        if expected_producers == 0 || self.tombstones.contains_key(signature) {
             return None;
         }
 
@@ -52,12 +147,7 @@ impl Comparator {
         let mut updated = false;
         entry
             .entry(endpoint.to_owned())
-            .and_modify(|existing| {
-                if data.elapsed_since_start < existing.elapsed_since_start {
-                    *existing = data.clone();
-                    updated = true;
-                }
-            })
+            .and_modify(|existing| updated = existing.merge(data.clone()))
             .or_insert_with(|| {
                 updated = true;
                 data.clone()
@@ -81,6 +171,90 @@ impl Comparator {
         }
     }
 
+    pub fn contains(&self, signature: &str) -> bool {
+        self.data.contains_key(signature)
+    }
+
+    /// Signatures first seen at or after `since` that some endpoint has not
+    /// delivered yet.
+    pub fn pending_since(&self, expected_producers: usize, since: Duration) -> usize {
+        self.data
+            .iter()
+            .filter(|entry| entry.value().len() < expected_producers)
+            .filter(|entry| !self.emitted.contains(entry.key()))
+            .filter(|entry| {
+                entry
+                    .value()
+                    .values()
+                    .map(TransactionData::arrival_elapsed)
+                    .min()
+                    .is_some_and(|first| first >= since)
+            })
+            .count()
+    }
+
+    /// Finalises signatures first seen before `deadline` that some endpoint
+    /// has still not delivered, returning their observations so far. Each
+    /// signature is returned at most once, here or by `record_observation`.
+    pub fn expire_incomplete(
+        &self,
+        expected_producers: usize,
+        deadline: Duration,
+    ) -> Vec<(String, HashMap<String, TransactionData>)> {
+        let expired: Vec<(String, HashMap<String, TransactionData>)> = self
+            .data
+            .iter()
+            .filter(|entry| entry.value().len() < expected_producers)
+            .filter(|entry| !self.emitted.contains(entry.key()))
+            .filter(|entry| {
+                entry
+                    .value()
+                    .values()
+                    .map(TransactionData::arrival_elapsed)
+                    .min()
+                    .is_some_and(|first| first < deadline)
+            })
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+
+        expired
+            .into_iter()
+            .filter(|(signature, _)| self.emitted.insert(signature.clone()))
+            .collect()
+    }
+
+    /// Moves every signature first seen before `cutoff` into a new comparator.
+    /// Evicted signatures are remembered for `tombstone_ttl` past `cutoff` so
+    /// late arrivals for them are ignored.
+    pub fn drain_before(&self, cutoff: Duration, tombstone_ttl: Duration) -> Comparator {
+        let drained = Comparator::new();
+        let expired: Vec<String> = self
+            .data
+            .iter()
+            .filter(|entry| {
+                entry
+                    .value()
+                    .values()
+                    .map(TransactionData::arrival_elapsed)
+                    .min()
+                    .is_some_and(|first| first < cutoff)
+            })
+            .map(|entry| entry.key().clone())
+            .collect();
+
+        let oldest_kept = cutoff.saturating_sub(tombstone_ttl);
+        self.tombstones
+            .retain(|_, evicted_at| *evicted_at >= oldest_kept);
+        for signature in expired {
+            self.emitted.remove(&signature);
+            if let Some((signature, observations)) = self.data.remove(&signature) {
+                self.tombstones.insert(signature.clone(), cutoff);
+                drained.data.insert(signature, observations);
+            }
+        }
+        drained
+    }
+
     pub fn iter(&self) -> dashmap::iter::Iter<'_, String, HashMap<String, TransactionData>> {
         self.data.iter()
     }
@@ -134,6 +308,178 @@ impl ProgressTracker {
     }
 }
 
+/// Connection and subscription times for one endpoint, since run start.
+#[derive(Debug, Clone, Default)]
+pub struct StreamSetup {
+    pub name: String,
+    pub connected_at: Option<Duration>,
+    pub subscribed_at: Option<Duration>,
+}
+
+/// Holds back signature counting until every endpoint has a live
+/// subscription, so an endpoint that connects late is not penalised for the
+/// signatures it never had a chance to see.
+#[derive(Debug)]
+pub struct StartBarrier {
+    start: Instant,
+    setups: Mutex<Vec<StreamSetup>>,
+    /// Nanoseconds after `start`, offset by one so zero means "still closed".
+    opened_ns: AtomicU64,
+}
+
+impl StartBarrier {
+    /// With `enabled` false the barrier starts open but setup times are still
+    /// recorded.
+    pub fn new(endpoint_names: &[String], start: Instant, enabled: bool) -> Self {
+        let barrier = Self {
+            start,
+            setups: Mutex::new(
+                endpoint_names
+                    .iter()
+                    .map(|name| StreamSetup {
+                        name: name.clone(),
+                        ..Default::default()
+                    })
+                    .collect(),
+            ),
+            opened_ns: AtomicU64::new(0),
+        };
+        if !enabled || endpoint_names.is_empty() {
+            barrier.open();
+        }
+        barrier
+    }
+
+    fn lock_setups(&self) -> std::sync::MutexGuard<'_, Vec<StreamSetup>> {
+        self.setups.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    pub fn connected(&self, endpoint: &str) {
+        let elapsed = self.start.elapsed();
+        if let Some(setup) = self
+            .lock_setups()
+            .iter_mut()
+            .find(|setup| setup.name == endpoint)
+        {
+            setup.connected_at.get_or_insert(elapsed);
+        }
+    }
+
+    /// Records a live subscription and opens the barrier once every endpoint
+    /// has one.
+    pub fn subscribed(&self, endpoint: &str) {
+        let elapsed = self.start.elapsed();
+        let all_subscribed = {
+            let mut setups = self.lock_setups();
+            if let Some(setup) = setups.iter_mut().find(|setup| setup.name == endpoint) {
+                setup.subscribed_at.get_or_insert(elapsed);
+            }
+            setups.iter().all(|setup| setup.subscribed_at.is_some())
+        };
+        if all_subscribed {
+            self.open();
+        }
+    }
+
+    /// Opens the barrier if it is still closed.
+    pub fn open(&self) {
+        let elapsed = self.start.elapsed().as_nanos() as u64;
+        let _ =
+            self.opened_ns
+                .compare_exchange(0, elapsed + 1, Ordering::AcqRel, Ordering::Acquire);
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.opened_ns.load(Ordering::Acquire) != 0
+    }
+
+    /// Time since run start at which counting began.
+    pub fn opened_at(&self) -> Option<Duration> {
+        match self.opened_ns.load(Ordering::Acquire) {
+            0 => None,
+            nanos => Some(Duration::from_nanos(nanos - 1)),
+        }
+    }
+
+    /// Range of first-seen times, in seconds since run start, to leave out of
+    /// the results: everything before the barrier opened.
+    pub fn excluded_range(&self) -> (f64, f64) {
+        let opened = self
+            .opened_at()
+            .map_or(f64::INFINITY, |at| at.as_secs_f64());
+        (0.0, opened)
+    }
+
+    /// Whether a signature with these observations counts, i.e. was first
+    /// seen after the barrier opened.
+    pub fn counts(&self, observations: &HashMap<String, TransactionData>) -> bool {
+        let first = observations
+            .values()
+            .map(TransactionData::arrival_elapsed)
+            .min();
+        matches!((self.opened_at(), first), (Some(opened), Some(first)) if first >= opened)
+    }
+
+    /// Endpoints that have not subscribed yet.
+    pub fn pending(&self) -> Vec<String> {
+        self.setups()
+            .into_iter()
+            .filter(|setup| setup.subscribed_at.is_none())
+            .map(|setup| setup.name)
+            .collect()
+    }
+
+    pub fn setups(&self) -> Vec<StreamSetup> {
+        self.lock_setups().clone()
+    }
+}
+
+/// Keeps streams open for a while after the signature target is reached so
+/// slower endpoints can deliver signatures the faster ones already reported.
+#[derive(Debug)]
+pub struct DrainGate {
+    timeout: Duration,
+    started: OnceLock<Instant>,
+    notify: Notify,
+}
+
+impl DrainGate {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            started: OnceLock::new(),
+            notify: Notify::new(),
+        }
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Starts draining. Returns false if draining had already started.
+    pub fn begin(&self) -> bool {
+        let started = self.started.set(Instant::now()).is_ok();
+        if started {
+            self.notify.notify_one();
+        }
+        started
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.started.get().is_some()
+    }
+
+    /// Resolves once draining has started, returning when it did.
+    pub async fn started(&self) -> Instant {
+        loop {
+            if let Some(started) = self.started.get() {
+                return *started;
+            }
+            self.notify.notified().await;
+        }
+    }
+}
+
 pub fn get_current_timestamp() -> f64 {
     let now = SystemTime::now();
     let since_epoch: Duration = match now.duration_since(UNIX_EPOCH) {
@@ -147,15 +493,19 @@ pub fn get_current_timestamp() -> f64 {
     since_epoch.as_secs_f64()
 }
 
+/// Percentile `p` in `[0, 1]` of ascending `sorted_data`, linearly
+/// interpolated between the two nearest samples.
 pub fn percentile(sorted_data: &[f64], p: f64) -> f64 {
     if sorted_data.is_empty() {
         return 0.0;
     }
-    let index = (p * (sorted_data.len() - 1) as f64).round() as usize;
-    sorted_data[index]
+    let rank = p.clamp(0.0, 1.0) * (sorted_data.len() - 1) as f64;
+    let lower = sorted_data[rank.floor() as usize];
+    let upper = sorted_data[rank.ceil() as usize];
+    lower + (upper - lower) * rank.fract()
 }
 
-pub fn open_log_file(name: &str) -> std::io::Result<impl Write> {
+pub fn open_log_file(name: &str) -> std::io::Result<File> {
     let safe_name = sanitize_filename(name);
     let log_filename = format!("transaction_log_{}.txt", safe_name);
     OpenOptions::new()
@@ -191,3 +541,29 @@ fn sanitize_filename(name: &str) -> String {
         trimmed.to_string()
     }
 }
+
+/// Small deterministic PRNG (SplitMix64) for reproducible synthetic data and
+/// resampling.
+#[derive(Debug, Clone)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
@@ -0,0 +1,162 @@
+//! Runs a short benchmark against two scripted mock feeds and checks the
+//! summary reflects their configured delays.
+
+use std::{
+    fs,
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+const BINARY: &str = env!("CARGO_BIN_EXE_geyserbench");
+const ACCOUNT: &str = "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA";
+const SLOW_DELAY_MS: f64 = 20.0;
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(20);
+const RUN_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Kills the child process when the test ends, even on panic.
+struct ChildGuard(Child);
+
+impl Drop for ChildGuard {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .map(|addr| addr.port())
+        .expect("free local port")
+}
+
+fn wait_for_port(port: u16) {
+    let deadline = Instant::now() + STARTUP_TIMEOUT;
+    while TcpStream::connect(("127.0.0.1", port)).is_err() {
+        assert!(
+            Instant::now() < deadline,
+            "mock server did not listen on {port}"
+        );
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+fn run_with_timeout(mut command: Command, dir: &Path) {
+    let mut child = ChildGuard(
+        command
+            .current_dir(dir)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("start benchmark"),
+    );
+    let deadline = Instant::now() + RUN_TIMEOUT;
+    loop {
+        if let Some(status) = child.0.try_wait().expect("poll benchmark") {
+            assert!(status.success(), "benchmark exited with {status}");
+            return;
+        }
+        assert!(Instant::now() < deadline, "benchmark did not finish");
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+#[test]
+fn benchmark_against_mock_servers_ranks_feeds_by_delay() {
+    let dir: PathBuf =
+        std::env::temp_dir().join(format!("geyserbench-mock-{}", std::process::id()));
+    fs::create_dir_all(&dir).expect("create test directory");
+    let (fast_port, slow_port) = (free_port(), free_port());
+
+    fs::write(
+        dir.join("mock.toml"),
+        format!(
+            r#"[script]
+accounts = ["{ACCOUNT}"]
+rate = 100.0
+seed = 7
+
+[[server]]
+kind = "yellowstone"
+listen = "127.0.0.1:{fast_port}"
+
+[[server]]
+kind = "arpc"
+listen = "127.0.0.1:{slow_port}"
+delay_ms = {SLOW_DELAY_MS}
+"#
+        ),
+    )
+    .expect("write mock config");
+    fs::write(
+        dir.join("config.toml"),
+        format!(
+            r#"[config]
+transactions = 100
+accounts = ["{ACCOUNT}"]
+commitment = "processed"
+
+[[endpoint]]
+name = "fast"
+url = "http://127.0.0.1:{fast_port}"
+kind = "yellowstone"
+
+[[endpoint]]
+name = "slow"
+url = "http://127.0.0.1:{slow_port}"
+kind = "arpc"
+
+[backend]
+enabled = false
+"#
+        ),
+    )
+    .expect("write benchmark config");
+
+    let _mock = ChildGuard(
+        Command::new(BINARY)
+            .args(["mock-server", "--config", "mock.toml"])
+            .current_dir(&dir)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("start mock server"),
+    );
+    wait_for_port(fast_port);
+    wait_for_port(slow_port);
+
+    let mut benchmark = Command::new(BINARY);
+    benchmark.args([
+        "--config",
+        "config.toml",
+        "--private",
+        "--metrics-out",
+        "metrics.json",
+    ]);
+    run_with_timeout(benchmark, &dir);
+
+    let metrics: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.join("metrics.json")).expect("read metrics"))
+            .expect("parse metrics");
+    let _ = fs::remove_dir_all(&dir);
+
+    assert!(metrics["total_signatures"].as_u64().unwrap() >= 100);
+    assert_eq!(metrics["fastest_endpoint"], "fast");
+
+    let fast = &metrics["per_endpoint"]["fast"];
+    let slow = &metrics["per_endpoint"]["slow"];
+    assert!(fast["first_detection_rate"].as_f64().unwrap() > 0.95);
+    // The first signature can reach the fast feed just before the slow one
+    // subscribes, and still be counted if the client handles it after the
+    // start barrier opened.
+    assert!(slow["missed_signatures"].as_u64().unwrap() <= 1);
+
+    let slow_p50 = slow["p50_latency_ms"].as_f64().unwrap();
+    assert!(
+        (SLOW_DELAY_MS * 0.5..SLOW_DELAY_MS * 3.0).contains(&slow_p50),
+        "slow feed P50 {slow_p50} ms is far from its {SLOW_DELAY_MS} ms delay"
+    );
+}